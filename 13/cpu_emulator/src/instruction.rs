@@ -78,7 +78,7 @@ impl Instruction {
         }
     }
 
-    fn decode_to_binary(instruction: &str) -> u16 {
+    pub(crate) fn decode_to_binary(instruction: &str) -> u16 {
         let mut inst: u16 = 0b0000_0000_0000_0000;
         let mut bit: u16 = 0b1000_0000_0000_0000;
         for (_, c) in instruction.chars().enumerate() {
@@ -10,6 +10,16 @@ use instruction::{
 
 use word::Word;
 
+// what step(true, ..) does besides sending the PC to 0
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ResetMode {
+    // only the PC, as the Hack hardware does; registers and RAM keep their values
+    #[default]
+    Pc,
+    // registers, RAM and screen are cleared first as if the power was switched off and on
+    PowerCycle,
+}
+
 #[derive(Clone)]
 pub struct Computer {
     pc: Word,
@@ -17,37 +27,118 @@ pub struct Computer {
     d: Word,
     ram: Vec<Word>,
     rom: Vec<Instruction>,
+    // the loaded words, for showing the program
+    rom_words: Vec<u16>,
     update_screen_addrs: Vec<u16>,
+    // the pixels of the screen words in the RAM, kept as they are written
+    screen: Vec<bool>,
+    // address and value of the RAM word written by the last step
+    last_write: Option<(u16, u16)>,
+    reset_mode: ResetMode,
 }
 
 impl Computer {
     pub fn new() -> Self {
-        Computer {
+        let mut computer = Computer {
             pc: Word::new(),
             a: Word::new(),
             d: Word::new(),
             ram: Vec::new(),
             rom: Vec::new(),
+            rom_words: Vec::new(),
             update_screen_addrs: Vec::new(),
-        }
+            screen: vec![false; 131072],
+            last_write: None,
+            reset_mode: ResetMode::default(),
+        };
+        computer.reset_ram();
+        computer
+    }
+
+    pub fn set_reset_mode(&mut self, reset_mode: ResetMode) {
+        self.reset_mode = reset_mode;
     }
 
     pub fn load_program(&mut self, instructions: Vec<&str>) {
         self.rom.clear();
+        self.rom_words.clear();
         for instruction in instructions {
             self.rom.push(Instruction::new(instruction));
+            self.rom_words.push(Instruction::decode_to_binary(instruction));
+        }
+    }
+
+    pub fn get_pc(&self) -> u16 {
+        self.pc.to_u16()
+    }
+
+    pub fn get_a(&self) -> u16 {
+        self.a.to_u16()
+    }
+
+    pub fn get_d(&self) -> u16 {
+        self.d.to_u16()
+    }
+
+    pub fn get_ram(&self, address: u16) -> u16 {
+        self.ram.get(address as usize).map(|word| word.to_u16()).unwrap_or(0)
+    }
+
+    // 0 past the end of the program
+    pub fn get_rom(&self, address: u16) -> u16 {
+        self.rom_words.get(address as usize).copied().unwrap_or(0)
+    }
+
+    // a word of the RAM or the screen, as a debugger edits memory; KBD is the keyboard's
+    pub fn set_ram(&mut self, address: u16, value: u16) {
+        if address >= 24576 /* KBD */ {
+            return;
+        }
+        self.ram[address as usize] = Word::from(value);
+        if address >= 16384 /* SCREEN */ {
+            self.update_screen_addrs.push(address);
+            Self::draw_word(&mut self.screen, address, value);
+        }
+    }
+
+    pub fn get_last_write(&self) -> Option<(u16, u16)> {
+        self.last_write
+    }
+
+    // the RAM and screen up to KBD, for snapshots
+    pub fn get_ram_words(&self) -> Vec<u16> {
+        self.ram[..24576 /* KBD */].iter().map(Word::to_u16).collect()
+    }
+
+    pub fn set_ram_words(&mut self, words: &[u16]) {
+        // up to KBD, so longer input can't wrap around to the low RAM
+        for (address, word) in words.iter().take(24576 /* KBD */).enumerate() {
+            self.set_ram(address as u16, *word);
         }
     }
 
+    // the loaded program, as many words as load_program was given, zeros at its end included
+    pub fn get_rom_words(&self) -> Vec<u16> {
+        self.rom_words.clone()
+    }
+
+    // where a snapshot was taken, the PC being the next instruction
+    pub fn set_registers(&mut self, pc: u16, a: u16, d: u16) {
+        self.pc = Word::from(pc);
+        self.a = Word::from(a);
+        self.d = Word::from(d);
+    }
+
     pub fn step(&mut self, reset: bool, key_code: u16) {
-        if reset {
-            self.reset_ram();
-            self.pc = Word::new();
+        if reset && self.reset_mode == ResetMode::PowerCycle {
+            self.power_cycle();
         }
 
         self.ram[24576 /* KBD */] = Word::from(key_code);
+        self.last_write = None;
 
-        let inst = &self.rom[self.pc.to_u16() as usize];
+        // past the program the ROM is 0, @0
+        let inst = self.rom.get(self.pc.to_u16() as usize).unwrap_or(&Instruction::A(0));
         match inst {
             Instruction::A(a) => {
                 self.a = Word::from(*a);
@@ -63,7 +154,9 @@ impl Computer {
                         self.ram[self.a.to_u16() as usize] = comp;
                         if Self::is_screen_addr(&self.a) {
                             self.update_screen_addrs.push(self.a.to_u16());
+                            Self::draw_word(&mut self.screen, self.a.to_u16(), comp.to_u16());
                         }
+                        self.last_write = Some((self.a.to_u16(), comp.to_u16()));
                     },
                     InstructionCDest::D             => {    /* D */
                         self.d = comp;
@@ -73,7 +166,9 @@ impl Computer {
                         self.ram[self.a.to_u16() as usize] = comp;
                         if Self::is_screen_addr(&self.a) {
                             self.update_screen_addrs.push(self.a.to_u16());
+                            Self::draw_word(&mut self.screen, self.a.to_u16(), comp.to_u16());
                         }
+                        self.last_write = Some((self.a.to_u16(), comp.to_u16()));
                     },
                     InstructionCDest::A             => {    /* A */
                         self.a = comp;
@@ -83,7 +178,9 @@ impl Computer {
                         self.ram[self.a.to_u16() as usize] = comp;
                         if Self::is_screen_addr(&self.a) {
                             self.update_screen_addrs.push(self.a.to_u16());
+                            Self::draw_word(&mut self.screen, self.a.to_u16(), comp.to_u16());
                         }
+                        self.last_write = Some((self.a.to_u16(), comp.to_u16()));
                     },
                     InstructionCDest::AAndD         => {    /* A, D */
                         self.a = comp;
@@ -95,14 +192,20 @@ impl Computer {
                         self.ram[self.a.to_u16() as usize] = comp;
                         if Self::is_screen_addr(&self.a) {
                             self.update_screen_addrs.push(self.a.to_u16());
+                            Self::draw_word(&mut self.screen, self.a.to_u16(), comp.to_u16());
                         }
+                        self.last_write = Some((self.a.to_u16(), comp.to_u16()));
                     },
                 }
-                println!("pc {}, a {:#018b}, d {:#018b}, comp {}", self.pc, self.a, self.d, comp);
 
                 self.pc = if Self::jump(jump, &comp) { self.a.clone() } else { self.pc + Word::from(1) };
             },
         }
+
+        // like the reset pin of the Hack CPU, the instruction still runs but the PC goes to 0
+        if reset {
+            self.pc = Word::new();
+        }
     }
 
     fn reset_ram(&mut self) {
@@ -112,6 +215,24 @@ impl Computer {
         }
     }
 
+    fn power_cycle(&mut self) {
+        self.pc = Word::new();
+        self.a = Word::new();
+        self.d = Word::new();
+        self.reset_ram();
+        // the cleared screen has to be drawn again
+        self.update_screen_addrs = (16384 /* SCREEN */..24576 /* KBD */).collect();
+        self.screen.fill(false);
+    }
+
+    // the 16 pixels of a screen word, the lowest bit leftmost
+    fn draw_word(screen: &mut [bool], addr: u16, word: u16) {
+        let base = (addr - 16384 /* SCREEN */) as usize * 16;
+        for (i, pixel) in screen[base..base + 16].iter_mut().enumerate() {
+            *pixel = word >> i & 1 != 0;
+        }
+    }
+
     fn is_screen_addr(addr: &Word) -> bool {
         let addr = addr.to_u16();
         addr >= 16384 /* SCREEN */ && addr <= 24575
@@ -163,21 +284,9 @@ impl Computer {
         }
     }
 
-    pub fn get_screen(&self) -> [bool; 131072] {
-        let mut screen = [false; 131072];
-        let mut x = 0;
-        for i in 0..8192 {
-            let word = self.ram[16384 /* SCREEN */ + i].to_u16();
-            let mut bit: u16 = 0b0000_0000_0000_0001;
-            for _ in 0..16 {
-                if word & bit != 0 {
-                    screen[x] = true;
-                }
-                bit <<= 1;
-                x += 1;
-            }
-        }
-        screen
+    // a pixel per element, row by row, true is black
+    pub fn screen(&self) -> &[bool] {
+        &self.screen
     }
 
     pub fn get_update_screen_pixels(&mut self) -> Vec<(i32, i32, bool)> {
@@ -275,4 +384,115 @@ mod tests {
         let comp = Word::from(1);   // 1
         assert_eq!(plus_one, Computer::jump(&input, &comp));
     }
-}
\ No newline at end of file
+
+    #[rstest]
+    #[case(ResetMode::Pc, (5, 5))]
+    #[case(ResetMode::PowerCycle, (0, 0))]
+    fn test_reset_mode(#[case] reset_mode: ResetMode, #[case] output: (u16, u16)) {
+        let mut computer = Computer::new();
+        computer.load_program(vec![
+            "0000000000000101", // @5
+            "1110110000010000", // D=A
+            "0000000000000000", // @0
+            "1110001100001000", // M=D
+            "0000000000000100", // (LOOP) @LOOP
+            "1110101010000111", // 0;JMP
+        ]);
+        computer.set_reset_mode(reset_mode);
+        for _ in 0..6 {
+            computer.step(false, 0);
+        }
+        computer.step(true, 0);
+        let (d, m) = output;
+        assert_eq!((0, d, m), (computer.pc.to_u16(), computer.d.to_u16(), computer.ram[0].to_u16()));
+    }
+
+    #[test]
+    fn test_state_getters() {
+        let mut computer = Computer::new();
+        computer.load_program(vec![
+            "0000000000010001", // @17
+            "1110110000010000", // D=A
+            "0000000000000011", // @3
+            "1110001100001000", // M=D
+        ]);
+        for _ in 0..3 {
+            computer.step(false, 0);
+        }
+        assert_eq!(None, computer.get_last_write());
+        computer.step(false, 0);
+        assert_eq!((4, 3, 17), (computer.get_pc(), computer.get_a(), computer.get_d()));
+        assert_eq!((17, 0), (computer.get_ram(3), computer.get_ram(65535)));
+        assert_eq!((0b1110_1100_0001_0000, 0), (computer.get_rom(1), computer.get_rom(4)));
+        assert_eq!(Some((3, 17)), computer.get_last_write());
+    }
+
+    #[test]
+    fn test_step_past_program() {
+        let mut computer = Computer::new();
+        computer.load_program(vec![
+            "0000000000000010", // @2
+            "1110110000010000", // D=A
+            "0000000000000011", // @3
+            "1110000010010000", // D=D+A
+            "0000000000000000", // @0
+            "1110001100001000", // M=D
+        ]);
+        for _ in 0..20 {
+            computer.step(false, 0);
+        }
+        assert_eq!((20, 0, 5), (computer.get_pc(), computer.get_a(), computer.get_ram(0)));
+
+        let mut computer = Computer::new();
+        computer.step(false, 0);
+        assert_eq!(1, computer.get_pc());
+    }
+
+    #[test]
+    fn test_screen() {
+        let mut computer = Computer::new();
+        computer.load_program(vec![
+            "0000000000000101", // @5
+            "1110110000010000", // D=A
+            "0100000000000001", // @16385
+            "1110001100001000", // M=D
+        ]);
+        for _ in 0..4 {
+            computer.step(false, 0);
+        }
+        let screen = computer.screen();
+        assert_eq!(131072, screen.len());
+        assert_eq!((true, false, true), (screen[16], screen[17], screen[18]));
+        assert_eq!(2, screen.iter().filter(|pixel| **pixel).count());
+
+        computer.set_reset_mode(ResetMode::PowerCycle);
+        computer.step(true, 0);
+        assert!(computer.screen().iter().all(|pixel| !pixel));
+    }
+
+    #[test]
+    fn test_snapshot_words() {
+        let mut computer = Computer::new();
+        computer.load_program(vec![
+            "0000000000000011", // @3
+            "1110001100001000", // M=D
+        ]);
+        assert_eq!(vec![3, 0b1110_0011_0000_1000], computer.get_rom_words());
+
+        let mut words = vec![0; 24576];
+        words[3] = 5;
+        words[24575] = 0b1000_0000_0000_0000;
+        computer.set_ram_words(&words);
+        assert_eq!(words, computer.get_ram_words());
+        assert!(computer.screen()[131071]);
+
+        computer.set_registers(1, 3, 7);
+        computer.step(false, 0);
+        assert_eq!((2, 3, 7, 7), (computer.get_pc(), computer.get_a(), computer.get_d(), computer.get_ram(3)));
+
+        let mut words = vec![1; 65537];
+        words[65536] = 9;
+        computer.set_ram_words(&words);
+        assert_eq!(1, computer.get_ram(0));
+    }
+}
@@ -1 +0,0 @@
-import("../pkg/index.js").catch(console.error);
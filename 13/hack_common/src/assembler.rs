@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use crate::disassembler::{comp, JUMP};
+
+// the Hack assembler: labels, predefined symbols and variables from RAM[16] on
+pub fn assemble(source: &str) -> Result<Vec<u16>, String> {
+    // instructions without comments and whitespace, with their line number
+    let mut lines = Vec::new();
+    let mut symbols = predefined_symbols();
+    for (i, line) in source.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or("");
+        let line: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(label) = line.strip_prefix('(').and_then(|line| line.strip_suffix(')')) {
+            if !is_symbol(label) {
+                return Err(format!("line {}: invalid label {}", i + 1, label));
+            }
+            if symbols.insert(label.to_string(), lines.len() as u16).is_some() {
+                return Err(format!("line {}: {} is already defined", i + 1, label));
+            }
+        } else {
+            lines.push((i + 1, line));
+        }
+    }
+
+    let mut next_variable = 16;
+    let mut instructions = Vec::new();
+    for (number, line) in lines {
+        let instruction = if let Some(value) = line.strip_prefix('@') {
+            if value.starts_with(|c: char| c.is_ascii_digit()) {
+                match value.parse::<u16>() {
+                    Ok(value) if value < 0b1000_0000_0000_0000 => value,
+                    _ => return Err(format!("line {}: {} is not a number from 0 to 32767", number, value)),
+                }
+            } else if is_symbol(value) {
+                *symbols.entry(value.to_string()).or_insert_with(|| {
+                    next_variable += 1;
+                    next_variable - 1
+                })
+            } else {
+                return Err(format!("line {}: invalid symbol {}", number, value));
+            }
+        } else {
+            c_instruction(&line).map_err(|e| format!("line {}: {}", number, e))?
+        };
+        instructions.push(instruction);
+    }
+    Ok(instructions)
+}
+
+// dest=comp;jump where dest and jump may be left out
+fn c_instruction(line: &str) -> Result<u16, String> {
+    let (dest, rest) = match line.split_once('=') {
+        Some((dest, rest)) => (dest, rest),
+        None => ("", line),
+    };
+    let (comp_text, jump) = match rest.split_once(';') {
+        Some((comp, jump)) => (comp, jump),
+        None => (rest, ""),
+    };
+
+    // the registers in any order, each once
+    let mut dest_bits = 0;
+    for register in dest.chars() {
+        let bit = match register {
+            'A' => 0b100,
+            'D' => 0b010,
+            'M' => 0b001,
+            _ => return Err(format!("invalid dest {}", dest)),
+        };
+        if dest_bits & bit != 0 {
+            return Err(format!("invalid dest {}", dest));
+        }
+        dest_bits |= bit;
+    }
+    if line.contains('=') && dest.is_empty() {
+        return Err(format!("missing dest in {}", line));
+    }
+    let comp_bits = (0..0b1000_0000)
+        .find(|bits| comp(*bits) == Some(comp_text))
+        .ok_or_else(|| format!("invalid comp {}", comp_text))?;
+    let jump_bits = match jump {
+        "" if line.contains(';') => return Err(format!("missing jump in {}", line)),
+        "" => 0,
+        _ => JUMP.iter().skip(1).position(|name| *name == jump).ok_or_else(|| format!("invalid jump {}", jump))? as u16 + 1,
+    };
+    Ok(0b1110_0000_0000_0000 | comp_bits << 6 | dest_bits << 3 | jump_bits)
+}
+
+// letters, digits, _ . $ : not starting with a digit
+fn is_symbol(text: &str) -> bool {
+    !text.is_empty()
+        && !text.starts_with(|c: char| c.is_ascii_digit())
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || "_.$:".contains(c))
+}
+
+fn predefined_symbols() -> HashMap<String, u16> {
+    let mut symbols: HashMap<String, u16> = [
+        ("SP", 0), ("LCL", 1), ("ARG", 2), ("THIS", 3), ("THAT", 4), ("SCREEN", 16384), ("KBD", 24576),
+    ]
+    .iter()
+    .map(|(name, address)| (name.to_string(), *address))
+    .collect();
+    for i in 0..16 {
+        symbols.insert(format!("R{}", i), i);
+    }
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+
+    const MAX: &str = "
+        // Computes R2 = max(R0, R1)
+        @R0
+        D=M              // D = first number
+        @R1
+        D=D-M            // D = first number - second number
+        @OUTPUT_FIRST
+        D;JGT            // if D>0 (first is greater) goto output_first
+        @R1
+        D=M              // D = second number
+        @OUTPUT_D
+        0;JMP            // goto output_d
+    (OUTPUT_FIRST)
+        @R0
+        D=M              // D = first number
+    (OUTPUT_D)
+        @R2
+        M=D              // M[2] = D (greatest number)
+    (INFINITE_LOOP)
+        @INFINITE_LOOP
+        0;JMP            // infinite loop
+    ";
+
+    fn words(hack: &str) -> Vec<u16> {
+        hack.lines().map(|line| u16::from_str_radix(line.trim(), 2).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_assemble_max() {
+        assert_eq!(Ok(words(include_str!("../../../05/Max.hack"))), assemble(MAX));
+    }
+
+    #[test]
+    fn test_assemble_fill() {
+        let fill = assemble(include_str!("../../../04/fill/Fill.asm")).unwrap();
+        assert_eq!(37, fill.len());
+        // @KBD, @SELECT_WHITE, @color, @pos, @n, D; JEQ
+        assert_eq!((24576, 8, 16, 17, 18), (fill[0], fill[2], fill[10], fill[14], fill[18]));
+        assert_eq!(0b1110_0011_0000_0010, fill[3]);
+    }
+
+    #[rstest]
+    #[case("@12345", Ok(vec![12345]))]
+    #[case("@SCREEN\n@KBD\n@THAT\n@R15", Ok(vec![16384, 24576, 4, 15]))]
+    #[case("@i\n@j\n@i", Ok(vec![16, 17, 16]))]
+    #[case("(END)\n@END\n0;JMP", Ok(vec![0, 0b1110_1010_1000_0111]))]
+    #[case("AMD = M + 1 ; JNE", Ok(vec![0b1111_1101_1111_1101]))]
+    #[case("DM=-1", Ok(vec![0b1110_1110_1001_1000]))]
+    #[case("@32768", Err("line 1: 32768 is not a number from 0 to 32767".to_string()))]
+    #[case("@1x", Err("line 1: 1x is not a number from 0 to 32767".to_string()))]
+    #[case("@a-b", Err("line 1: invalid symbol a-b".to_string()))]
+    #[case("(LOOP)\n(LOOP)", Err("line 2: LOOP is already defined".to_string()))]
+    #[case("(1LOOP)", Err("line 1: invalid label 1LOOP".to_string()))]
+    #[case("\n\nD=A+D", Err("line 3: invalid comp A+D".to_string()))]
+    #[case("AA=1", Err("line 1: invalid dest AA".to_string()))]
+    #[case("=1", Err("line 1: missing dest in =1".to_string()))]
+    #[case("0;JXX", Err("line 1: invalid jump JXX".to_string()))]
+    #[case("0;", Err("line 1: missing jump in 0;".to_string()))]
+    fn test_assemble(#[case] input: &str, #[case] output: Result<Vec<u16>, String>) {
+        assert_eq!(output, assemble(input));
+    }
+}
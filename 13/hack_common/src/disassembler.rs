@@ -0,0 +1,86 @@
+// Hack assembly of one instruction, e.g. "@17", "D=D-A" or "0;JMP";
+// None for words that are neither an A- nor a valid C-instruction
+pub fn disassemble(instruction: u16) -> Option<String> {
+    if instruction & 0b1000_0000_0000_0000 == 0 {
+        return Some(format!("@{}", instruction));
+    }
+    if instruction & 0b1110_0000_0000_0000 != 0b1110_0000_0000_0000 {
+        return None;
+    }
+    let comp = comp((instruction & 0b0001_1111_1100_0000) >> 6)?;
+    let dest = DEST[((instruction & 0b0000_0000_0011_1000) >> 3) as usize];
+    let jump = JUMP[(instruction & 0b0000_0000_0000_0111) as usize];
+    let mut text = String::new();
+    if !dest.is_empty() {
+        text.push_str(dest);
+        text.push('=');
+    }
+    text.push_str(comp);
+    if !jump.is_empty() {
+        text.push(';');
+        text.push_str(jump);
+    }
+    Some(text)
+}
+
+pub(crate) const DEST: [&str; 8] = ["", "M", "D", "MD", "A", "AM", "AD", "AMD"];
+pub(crate) const JUMP: [&str; 8] = ["", "JGT", "JEQ", "JGE", "JLT", "JNE", "JLE", "JMP"];
+
+pub(crate) fn comp(bits: u16) -> Option<&'static str> {
+    match bits {
+        0b0_101010 => Some("0"),
+        0b0_111111 => Some("1"),
+        0b0_111010 => Some("-1"),
+        0b0_001100 => Some("D"),
+        0b0_110000 => Some("A"),
+        0b1_110000 => Some("M"),
+        0b0_001101 => Some("!D"),
+        0b0_110001 => Some("!A"),
+        0b1_110001 => Some("!M"),
+        0b0_001111 => Some("-D"),
+        0b0_110011 => Some("-A"),
+        0b1_110011 => Some("-M"),
+        0b0_011111 => Some("D+1"),
+        0b0_110111 => Some("A+1"),
+        0b1_110111 => Some("M+1"),
+        0b0_001110 => Some("D-1"),
+        0b0_110010 => Some("A-1"),
+        0b1_110010 => Some("M-1"),
+        0b0_000010 => Some("D+A"),
+        0b1_000010 => Some("D+M"),
+        0b0_010011 => Some("D-A"),
+        0b1_010011 => Some("D-M"),
+        0b0_000111 => Some("A-D"),
+        0b1_000111 => Some("M-D"),
+        0b0_000000 => Some("D&A"),
+        0b1_000000 => Some("D&M"),
+        0b0_010101 => Some("D|A"),
+        0b1_010101 => Some("D|M"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+
+    #[rstest]
+    #[case(0b0000_0000_0000_0000, Some("@0"))]
+    #[case(0b0011_0000_0011_1001, Some("@12345"))]
+    #[case(0b0111_1111_1111_1111, Some("@32767"))]
+    #[case(0b1110_1100_0001_0000, Some("D=A"))]
+    #[case(0b1110_0100_1101_0000, Some("D=D-A"))]
+    #[case(0b1111_1100_0001_0000, Some("D=M"))]
+    #[case(0b1110_1010_1000_0111, Some("0;JMP"))]
+    #[case(0b1110_0011_0000_0010, Some("D;JEQ"))]
+    #[case(0b1111_1101_1100_1000, Some("M=M+1"))]
+    #[case(0b1111_1100_1010_1000, Some("AM=M-1"))]
+    #[case(0b1110_1110_1011_1101, Some("AMD=-1;JNE"))]
+    #[case(0b1110_1010_1000_0000, Some("0"))]
+    #[case(0b1100_1100_0001_0000, None)]
+    #[case(0b1110_1111_1001_0000, None)]
+    fn test_disassemble(#[case] input: u16, #[case] output: Option<&str>) {
+        assert_eq!(output.map(String::from), disassemble(input));
+    }
+}
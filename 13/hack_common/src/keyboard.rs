@@ -0,0 +1,230 @@
+use std::collections::VecDeque;
+
+// key codes of the Hack keyboard; printable characters use their ASCII code
+pub const NEWLINE: u16 = 128;
+pub const BACKSPACE: u16 = 129;
+pub const LEFT_ARROW: u16 = 130;
+pub const UP_ARROW: u16 = 131;
+pub const RIGHT_ARROW: u16 = 132;
+pub const DOWN_ARROW: u16 = 133;
+pub const HOME: u16 = 134;
+pub const END: u16 = 135;
+pub const PAGE_UP: u16 = 136;
+pub const PAGE_DOWN: u16 = 137;
+pub const INSERT: u16 = 138;
+pub const DELETE: u16 = 139;
+pub const ESC: u16 = 140;
+pub const F1: u16 = 141;
+pub const F12: u16 = 152;
+
+pub fn char_code(c: char) -> Option<u16> {
+    if (' '..='~').contains(&c) {
+        Some(c as u16)
+    } else {
+        None
+    }
+}
+
+// the character a key gives together with shift on a US keyboard
+pub fn shifted(c: char) -> char {
+    match c {
+        'a'..='z' => c.to_ascii_uppercase(),
+        '`' => '~',
+        '1' => '!',
+        '2' => '@',
+        '3' => '#',
+        '4' => '$',
+        '5' => '%',
+        '6' => '^',
+        '7' => '&',
+        '8' => '*',
+        '9' => '(',
+        '0' => ')',
+        '-' => '_',
+        '=' => '+',
+        '[' => '{',
+        ']' => '}',
+        '\\' => '|',
+        ';' => ':',
+        '\'' => '"',
+        ',' => '<',
+        '.' => '>',
+        '/' => '?',
+        _ => c,
+    }
+}
+
+// key names as in KeyboardEvent.key: the typed character, or the name of a special key
+pub fn key_code(key: &str) -> Option<u16> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return char_code(c);
+    }
+    match key {
+        "Enter" => Some(NEWLINE),
+        "Backspace" => Some(BACKSPACE),
+        "ArrowLeft" => Some(LEFT_ARROW),
+        "ArrowUp" => Some(UP_ARROW),
+        "ArrowRight" => Some(RIGHT_ARROW),
+        "ArrowDown" => Some(DOWN_ARROW),
+        "Home" => Some(HOME),
+        "End" => Some(END),
+        "PageUp" => Some(PAGE_UP),
+        "PageDown" => Some(PAGE_DOWN),
+        "Insert" => Some(INSERT),
+        "Delete" => Some(DELETE),
+        "Escape" => Some(ESC),
+        _ => match key.strip_prefix('F').map(|n| n.parse::<u16>()) {
+            Some(Ok(n)) if (1..=12).contains(&n) => Some(F1 + n - 1),
+            _ => None,
+        },
+    }
+}
+
+// keys held down, identified by physical key (scancode, KeyboardEvent.code);
+// the KBD register shows the one pressed last
+#[derive(Debug, Clone, Default)]
+pub struct PressedKeys {
+    keys: Vec<(String, u16)>,
+}
+
+impl PressedKeys {
+    pub fn new() -> Self {
+        PressedKeys { keys: Vec::new() }
+    }
+
+    pub fn press(&mut self, key: &str, code: u16) {
+        self.release(key);
+        self.keys.push((key.to_string(), code));
+    }
+
+    pub fn release(&mut self, key: &str) {
+        self.keys.retain(|(k, _)| k != key);
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    pub fn code(&self) -> u16 {
+        self.keys.last().map(|(_, code)| *code).unwrap_or(0)
+    }
+}
+
+// text typed into the KBD register a character at a time: each key is held down for some cycles
+// and then released for as many, so a program waiting for a key and for its release sees every one
+#[derive(Debug, Clone)]
+pub struct TypedText {
+    codes: VecDeque<u16>,
+    cycles_per_key: u32,
+    cycle: u32,
+}
+
+impl TypedText {
+    // \n is Enter; characters without a Hack key code, like \r, are left out
+    pub fn new(text: &str, cycles_per_key: u32) -> Self {
+        TypedText {
+            codes: text.chars().filter_map(|c| if c == '\n' { Some(NEWLINE) } else { char_code(c) }).collect(),
+            cycles_per_key: cycles_per_key.clamp(1, u32::MAX / 2),
+            cycle: 0,
+        }
+    }
+
+    // the key down for the next cycle, 0 between keys; None once everything is typed
+    pub fn next_key(&mut self) -> Option<u16> {
+        let code = *self.codes.front()?;
+        let key = if self.cycle < self.cycles_per_key { code } else { 0 };
+        self.cycle += 1;
+        if self.cycle == 2 * self.cycles_per_key {
+            self.codes.pop_front();
+            self.cycle = 0;
+        }
+        Some(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+
+    #[rstest]
+    #[case(' ', Some(32))]
+    #[case('0', Some(48))]
+    #[case('A', Some(65))]
+    #[case('z', Some(122))]
+    #[case('~', Some(126))]
+    #[case('\n', None)]
+    #[case('é', None)]
+    fn test_char_code(#[case] input: char, #[case] output: Option<u16>) {
+        assert_eq!(output, char_code(input));
+    }
+
+    #[rstest]
+    #[case('a', 'A')]
+    #[case('1', '!')]
+    #[case('/', '?')]
+    #[case('\'', '"')]
+    #[case('A', 'A')]
+    #[case(' ', ' ')]
+    fn test_shifted(#[case] input: char, #[case] output: char) {
+        assert_eq!(output, shifted(input));
+    }
+
+    #[rstest]
+    #[case("a", Some(97))]
+    #[case("A", Some(65))]
+    #[case(" ", Some(32))]
+    #[case("#", Some(35))]
+    #[case("Enter", Some(128))]
+    #[case("Backspace", Some(129))]
+    #[case("ArrowLeft", Some(130))]
+    #[case("ArrowUp", Some(131))]
+    #[case("ArrowRight", Some(132))]
+    #[case("ArrowDown", Some(133))]
+    #[case("Home", Some(134))]
+    #[case("End", Some(135))]
+    #[case("PageUp", Some(136))]
+    #[case("PageDown", Some(137))]
+    #[case("Insert", Some(138))]
+    #[case("Delete", Some(139))]
+    #[case("Escape", Some(140))]
+    #[case("F1", Some(141))]
+    #[case("F12", Some(152))]
+    #[case("F13", None)]
+    #[case("F", Some(70))]
+    #[case("Shift", None)]
+    #[case("Tab", None)]
+    fn test_key_code(#[case] input: &str, #[case] output: Option<u16>) {
+        assert_eq!(output, key_code(input));
+    }
+
+    #[test]
+    fn test_pressed_keys() {
+        let mut keys = PressedKeys::new();
+        assert_eq!(0, keys.code());
+        keys.press("KeyA", 97);
+        keys.press("KeyB", 66);
+        assert_eq!(66, keys.code());
+        keys.press("KeyA", 65);
+        assert_eq!(65, keys.code());
+        keys.release("KeyA");
+        assert_eq!(66, keys.code());
+        keys.release("KeyB");
+        assert_eq!(0, keys.code());
+        keys.press("KeyC", 99);
+        keys.clear();
+        assert_eq!(0, keys.code());
+    }
+
+    #[test]
+    fn test_typed_text() {
+        let mut text = TypedText::new("a\r\nB\té", 2);
+        let keys: Vec<u16> = std::iter::from_fn(|| text.next_key()).collect();
+        assert_eq!(vec![97, 97, 0, 0, 128, 128, 0, 0, 66, 66, 0, 0], keys);
+        assert_eq!(None, text.next_key());
+
+        let mut text = TypedText::new("x", 0);
+        assert_eq!((Some(120), Some(0), None), (text.next_key(), text.next_key(), text.next_key()));
+    }
+}
@@ -0,0 +1,9 @@
+pub mod assembler;
+pub mod disassembler;
+pub mod font;
+pub mod keyboard;
+pub mod link;
+pub mod machine;
+pub mod program;
+pub mod screenshot;
+pub mod snapshot;
@@ -0,0 +1,110 @@
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+
+// a .hack or .asm program in the fragment of a page address, deflated and in URL-safe base64:
+//   index.html#program=<text>
+const KEY: &str = "program";
+
+// more than the .hack text of 32768 instructions
+const MAX_TEXT: usize = 32768 * 18;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// the fragment for the text of a program, without the #
+pub fn to_fragment(text: &str) -> String {
+    format!("{}={}", KEY, encode(&compress_to_vec(text.as_bytes(), 9)))
+}
+
+// the text of the program in a fragment like #program=..., None when there is none
+pub fn from_fragment(fragment: &str) -> Option<Result<String, String>> {
+    let value = fragment.trim_start_matches('#').split('&').find_map(|parameter| {
+        let (key, value) = parameter.split_once('=')?;
+        (key == KEY).then_some(value)
+    })?;
+    Some(decode(value))
+}
+
+// the .hack text of the instructions in a ROM, without the zeros after them
+pub fn hack_text(rom: &[u16]) -> String {
+    let end = rom.iter().rposition(|word| *word != 0).map_or(0, |i| i + 1);
+    rom[..end].iter().map(|word| format!("{:016b}\n", word)).collect()
+}
+
+fn decode(value: &str) -> Result<String, String> {
+    let bytes = decode_base64(value).ok_or("the program link is damaged")?;
+    let text = decompress_to_vec_with_limit(&bytes, MAX_TEXT).map_err(|_| "the program link is damaged")?;
+    String::from_utf8(text).map_err(|_| "the program link is not text".to_string())
+}
+
+// without padding
+fn encode(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| bits | (u32::from(*byte) << (16 - 8 * i)));
+        for i in 0..=chunk.len() {
+            text.push(BASE64[((bits >> (18 - 6 * i)) & 0x3f) as usize] as char);
+        }
+    }
+    text
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut bits = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = BASE64.iter().position(|b| b == c)? as u32;
+            bits |= value << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            bytes.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+
+    #[rstest]
+    #[case(b"", "")]
+    #[case(b"f", "Zg")]
+    #[case(b"fo", "Zm8")]
+    #[case(b"foo", "Zm9v")]
+    #[case(b"\xfb\xff", "-_8")]
+    fn test_base64(#[case] bytes: &[u8], #[case] text: &str) {
+        assert_eq!(text, encode(bytes));
+        assert_eq!(Some(bytes.to_vec()), decode_base64(text));
+    }
+
+    #[rstest]
+    #[case("@2\nD=A // D = 2\n")]
+    #[case("0000000000000010\n1110110000010000\n")]
+    fn test_fragment(#[case] text: &str) {
+        let fragment = to_fragment(text);
+        assert!(fragment.starts_with("program="));
+        assert_eq!(Some(Ok(text.to_string())), from_fragment(&format!("#{}", fragment)));
+        assert_eq!(Some(Ok(text.to_string())), from_fragment(&format!("x=1&{}", fragment)));
+    }
+
+    #[rstest]
+    #[case("", None)]
+    #[case("#top", None)]
+    #[case("#program=a", Some(Err("the program link is damaged".to_string())))]
+    #[case("#program=a*bc", Some(Err("the program link is damaged".to_string())))]
+    #[case("#program=AAAA", Some(Err("the program link is damaged".to_string())))]
+    fn test_from_fragment(#[case] fragment: &str, #[case] output: Option<Result<String, String>>) {
+        assert_eq!(output, from_fragment(fragment));
+    }
+
+    #[test]
+    fn test_hack_text() {
+        assert_eq!("0000000000000010\n0000000000000000\n1110110000010000\n", hack_text(&[2, 0, 0xec10, 0, 0]));
+        assert_eq!("", hack_text(&[0, 0]));
+    }
+}
@@ -0,0 +1,275 @@
+use std::fmt;
+use std::str::FromStr;
+
+pub use cpu_emulator::ResetMode;
+
+// what a frontend needs from a Hack computer, whichever backend runs it
+pub trait Machine {
+    fn load_program(&mut self, instructions: Vec<&str>);
+    fn set_reset_mode(&mut self, reset_mode: ResetMode);
+    fn step(&mut self, reset: bool, key_code: u16);
+    // a pixel per element, row by row, true is black
+    fn screen(&self) -> &[bool];
+    // the pixels written since the last call, or None when only screen knows
+    fn get_update_screen_pixels(&mut self) -> Option<Vec<(i32, i32, bool)>> {
+        None
+    }
+    fn get_pc(&self) -> u16;
+    fn get_a(&self) -> u16;
+    fn get_d(&self) -> u16;
+    fn get_ram(&self, address: u16) -> u16;
+    fn get_rom(&self, address: u16) -> u16;
+    // writes a RAM or screen word between steps; KBD and above are left to the keyboard
+    fn set_ram(&mut self, address: u16, value: u16);
+    // address and value of the RAM word written by the last step
+    fn get_last_write(&self) -> Option<(u16, u16)>;
+    // the RAM and screen up to KBD, for snapshots
+    fn get_ram_words(&self) -> Vec<u16>;
+    fn set_ram_words(&mut self, words: &[u16]);
+    // the words of the last load_program, as many as it was given, zeros at the end included
+    fn get_rom_words(&self) -> Vec<u16>;
+    fn set_registers(&mut self, pc: u16, a: u16, d: u16);
+}
+
+impl Machine for cpu_emulator::Computer {
+    fn load_program(&mut self, instructions: Vec<&str>) {
+        self.load_program(instructions)
+    }
+
+    fn set_reset_mode(&mut self, reset_mode: ResetMode) {
+        self.set_reset_mode(reset_mode)
+    }
+
+    fn step(&mut self, reset: bool, key_code: u16) {
+        self.step(reset, key_code)
+    }
+
+    fn screen(&self) -> &[bool] {
+        self.screen()
+    }
+
+    fn get_update_screen_pixels(&mut self) -> Option<Vec<(i32, i32, bool)>> {
+        Some(self.get_update_screen_pixels())
+    }
+
+    fn get_pc(&self) -> u16 {
+        self.get_pc()
+    }
+
+    fn get_a(&self) -> u16 {
+        self.get_a()
+    }
+
+    fn get_d(&self) -> u16 {
+        self.get_d()
+    }
+
+    fn get_ram(&self, address: u16) -> u16 {
+        self.get_ram(address)
+    }
+
+    fn get_rom(&self, address: u16) -> u16 {
+        self.get_rom(address)
+    }
+
+    fn set_ram(&mut self, address: u16, value: u16) {
+        self.set_ram(address, value)
+    }
+
+    fn get_last_write(&self) -> Option<(u16, u16)> {
+        self.get_last_write()
+    }
+
+    fn get_ram_words(&self) -> Vec<u16> {
+        self.get_ram_words()
+    }
+
+    fn set_ram_words(&mut self, words: &[u16]) {
+        self.set_ram_words(words)
+    }
+
+    fn get_rom_words(&self) -> Vec<u16> {
+        self.get_rom_words()
+    }
+
+    fn set_registers(&mut self, pc: u16, a: u16, d: u16) {
+        self.set_registers(pc, a, d)
+    }
+}
+
+// the gate-level and built-in hardware computers have the same methods
+macro_rules! impl_hardware_machine {
+    ($computer:ty) => {
+        impl Machine for $computer {
+            fn load_program(&mut self, instructions: Vec<&str>) {
+                self.load_program(instructions)
+            }
+
+            fn set_reset_mode(&mut self, reset_mode: ResetMode) {
+                self.set_reset_mode(reset_mode)
+            }
+
+            fn step(&mut self, reset: bool, key_code: u16) {
+                self.step(reset, key_code)
+            }
+
+            fn screen(&self) -> &[bool] {
+                self.screen()
+            }
+
+            fn get_pc(&self) -> u16 {
+                self.get_pc()
+            }
+
+            fn get_a(&self) -> u16 {
+                self.get_a()
+            }
+
+            fn get_d(&self) -> u16 {
+                self.get_d()
+            }
+
+            fn get_ram(&self, address: u16) -> u16 {
+                self.get_ram(address)
+            }
+
+            fn get_rom(&self, address: u16) -> u16 {
+                self.get_rom(address)
+            }
+
+            fn set_ram(&mut self, address: u16, value: u16) {
+                self.set_ram(address, value)
+            }
+
+            fn get_last_write(&self) -> Option<(u16, u16)> {
+                self.get_last_write()
+            }
+
+            fn get_ram_words(&self) -> Vec<u16> {
+                self.get_ram_words()
+            }
+
+            fn set_ram_words(&mut self, words: &[u16]) {
+                self.set_ram_words(words)
+            }
+
+            fn get_rom_words(&self) -> Vec<u16> {
+                self.get_rom_words()
+            }
+
+            fn set_registers(&mut self, pc: u16, a: u16, d: u16) {
+                self.set_registers(pc, a, d)
+            }
+        }
+    };
+}
+
+impl_hardware_machine!(hardware_simulator::Computer);
+impl_hardware_machine!(hardware_simulator::ComputerBuiltIn);
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Backend {
+    // cpu_emulator: the instructions are executed directly
+    #[default]
+    Emulator,
+    // hardware_simulator with the built-in chips
+    BuiltIn,
+    // hardware_simulator down to the Nand gates
+    Gates,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 3] = [Backend::Emulator, Backend::BuiltIn, Backend::Gates];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Emulator => "emulator",
+            Backend::BuiltIn => "builtin",
+            Backend::Gates => "gates",
+        }
+    }
+
+    pub fn create(self) -> Box<dyn Machine> {
+        match self {
+            Backend::Emulator => Box::new(cpu_emulator::Computer::new()),
+            Backend::BuiltIn => Box::new(hardware_simulator::ComputerBuiltIn::new()),
+            Backend::Gates => Box::new(hardware_simulator::Computer::new()),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Backend::ALL
+            .into_iter()
+            .find(|backend| backend.name() == name)
+            .ok_or_else(|| format!("unknown backend {}, use emulator, builtin or gates", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+
+    // @17, D=A, @3, M=D
+    const STORE_17: [&str; 4] = ["0000000000010001", "1110110000010000", "0000000000000011", "1110001100001000"];
+
+    #[rstest]
+    #[case("emulator", Ok(Backend::Emulator))]
+    #[case("builtin", Ok(Backend::BuiltIn))]
+    #[case("gates", Ok(Backend::Gates))]
+    #[case("nand", Err("unknown backend nand, use emulator, builtin or gates".to_string()))]
+    fn test_backend_from_str(#[case] input: &str, #[case] output: Result<Backend, String>) {
+        assert_eq!(output, input.parse::<Backend>());
+    }
+
+    #[rstest]
+    #[case(Backend::Emulator)]
+    #[case(Backend::BuiltIn)]
+    #[case(Backend::Gates)]
+    fn test_machine(#[case] backend: Backend) {
+        let mut machine = backend.create();
+        machine.load_program(STORE_17.to_vec());
+        machine.step(true, 0);
+        for _ in 0..4 {
+            machine.step(false, 0);
+        }
+        assert_eq!((4, 3, 17, 17), (machine.get_pc(), machine.get_a(), machine.get_d(), machine.get_ram(3)));
+        assert_eq!(0b1110_1100_0001_0000, machine.get_rom(1));
+        assert_eq!(Some((3, 17)), machine.get_last_write());
+
+        machine.set_ram(3, 5);
+        machine.set_ram(16384, 1);
+        machine.set_ram(24576, 1);
+        assert_eq!((5, 1, 0), (machine.get_ram(3), machine.get_ram(16384), machine.get_ram(24576)));
+        assert!(machine.screen()[0]);
+
+        machine.set_reset_mode(ResetMode::PowerCycle);
+        machine.step(true, 0);
+        assert_eq!((0, 0, 0), (machine.get_pc(), machine.get_d(), machine.get_ram(3)));
+    }
+
+    #[rstest]
+    #[case(Backend::Emulator)]
+    #[case(Backend::BuiltIn)]
+    #[case(Backend::Gates)]
+    fn test_machine_set_ram(#[case] backend: Backend) {
+        let mut machine = backend.create();
+        // @3, D=M
+        machine.load_program(vec!["0000000000000011", "1111110000010000", "0000000000000000"]);
+        machine.step(true, 0);
+        machine.step(false, 0);
+        machine.set_ram(3, 9);
+        machine.step(false, 0);
+        assert_eq!((9, 9), (machine.get_ram(3), machine.get_d()));
+    }
+}
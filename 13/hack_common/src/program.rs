@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use crate::assembler;
+
+pub const MAX_INSTRUCTIONS: usize = 32768;
+
+// a .hack file has one 16-bit instruction per line, written in 0 and 1
+pub fn parse_hack(text: &str) -> Result<Vec<String>, String> {
+    let mut instructions = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.len() != 16 || !line.chars().all(|c| c == '0' || c == '1') {
+            return Err(format!("line {}: not a 16-bit instruction: {}", i + 1, line));
+        }
+        instructions.push(line.to_string());
+    }
+    Ok(instructions)
+}
+
+// the instructions of a .hack or .asm program, as load_program takes them
+pub fn parse(name: &str, text: &str) -> Result<Vec<String>, String> {
+    let instructions = if is_asm(name) {
+        assembler::assemble(text)?.iter().map(|instruction| format!("{:016b}", instruction)).collect()
+    } else {
+        parse_hack(text)?
+    };
+    if instructions.is_empty() || instructions.len() > MAX_INSTRUCTIONS {
+        return Err(format!("a program needs 1 to {} instructions", MAX_INSTRUCTIONS));
+    }
+    Ok(instructions)
+}
+
+// text without a file name, as pasted into a web page: .hack when it is all 0 and 1
+pub fn parse_text(text: &str) -> Result<Vec<String>, String> {
+    let name = if parse_hack(text).is_ok() { "program.hack" } else { "program.asm" };
+    parse(name, text)
+}
+
+// Fill of project 4, the program run before one is loaded: the screen is black, and white while a key is down
+pub const FILL: &[&str] = &[
+                        //(LOOP_KBD)
+    "0110000000000000", //        @KBD
+    "1111110000010000", //        D=M
+    "0000000000001000", //        @SELECT_BLACK
+    "1110001100000010", //        D; JEQ
+    "0000000000000000", //        @0
+    "1110110000010000", //        D=A
+    "0000000000001010", //        @SET_COLOR
+    "1110101010000111", //        0; JMP
+                        //(SELECT_BLACK)
+    "0000000000000000", //        @0
+    "1110110010010000", //        D=A-1
+                        //(SET_COLOR)
+    "0000000000010000", //        @color
+    "1110001100001000", //        M=D
+
+    "0100000000000000", //        @SCREEN
+    "1110110000010000", //        D=A
+    "0000000000010001", //        @pos
+    "1110001100001000", //        M=D
+
+                        //        // 32 * 256 = 8192
+    "0010000000000000", //        @8192
+    "1110110000010000", //        D=A
+    "0000000000010010", //        @n
+    "1110001100001000", //        M=D
+
+                        //(LOOP_FILL)
+    "0000000000010010", //        @n
+    "1111110000010000", //        D=M
+    "0000000000100011", //        @FILL_END
+    "1110001100000010", //        D; JEQ
+
+                        //        // print color
+    "0000000000010000", //        @color
+    "1111110000010000", //        D=M
+    "0000000000010001", //        @pos
+    "1111110000100000", //        A=M
+    "1110001100001000", //        M=D
+
+    "0000000000010001", //        @pos
+    "1111110111001000", //        M=M+1
+    "0000000000010010", //        @n
+    "1111110010001000", //        M=M-1
+
+    "0000000000010100", //        @LOOP_FILL
+    "1110101010000111", //        0; JMP
+                        //(FILL_END)
+    "0000000000000000", //        @LOOP_KBD
+    "1110101010000111", //        0; JMP
+];
+
+pub fn load(path: &Path) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse(&path.to_string_lossy(), &text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn is_asm(name: &str) -> bool {
+    name.to_ascii_lowercase().ends_with(".asm")
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+
+    #[rstest]
+    #[case("Add.hack", "0000000000000010\n\n1110110000010000\n", Ok(vec!["0000000000000010", "1110110000010000"]))]
+    #[case("Add.asm", "@2\nD=A // D = 2\n", Ok(vec!["0000000000000010", "1110110000010000"]))]
+    #[case("Add.hack", "000000000000001\n", Err("line 1: not a 16-bit instruction: 000000000000001"))]
+    #[case("Add.hack", "@2\n", Err("line 1: not a 16-bit instruction: @2"))]
+    #[case("Add.asm", "@2\nD=X\n", Err("line 2: invalid comp X"))]
+    #[case("Empty.hack", "\n", Err("a program needs 1 to 32768 instructions"))]
+    #[case("Empty.ASM", "// nothing\n", Err("a program needs 1 to 32768 instructions"))]
+    fn test_parse(#[case] name: &str, #[case] text: &str, #[case] output: Result<Vec<&str>, &str>) {
+        let output = output.map(|lines| lines.into_iter().map(String::from).collect()).map_err(String::from);
+        assert_eq!(output, parse(name, text));
+    }
+
+    #[rstest]
+    #[case("0000000000000010\n1110110000010000\n", Ok(vec!["0000000000000010", "1110110000010000"]))]
+    #[case("@2\nD=A\n", Ok(vec!["0000000000000010", "1110110000010000"]))]
+    #[case("0000000000000010\nD=X\n", Err("line 1: invalid comp 0000000000000010"))]
+    #[case("", Err("a program needs 1 to 32768 instructions"))]
+    fn test_parse_text(#[case] text: &str, #[case] output: Result<Vec<&str>, &str>) {
+        let output = output.map(|lines| lines.into_iter().map(String::from).collect()).map_err(String::from);
+        assert_eq!(output, parse_text(text));
+    }
+
+    #[test]
+    fn test_load() {
+        assert_eq!(16, load(Path::new("../../05/Max.hack")).unwrap().len());
+        assert!(load(Path::new("../../05/Missing.hack")).unwrap_err().starts_with("../../05/Missing.hack: "));
+    }
+
+    #[test]
+    fn test_fill() {
+        let mut machine = crate::machine::Backend::Emulator.create();
+        machine.load_program(FILL.to_vec());
+        machine.step(true, 0);
+        for _ in 0..100 {
+            machine.step(false, 0);
+        }
+        // the first words of the screen are black, and the rest still white
+        assert!(machine.screen()[..32].iter().all(|black| *black));
+        assert!(!machine.screen()[512 * 255]);
+    }
+}
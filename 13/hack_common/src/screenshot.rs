@@ -0,0 +1,247 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub const WIDTH: usize = 512;
+pub const HEIGHT: usize = 256;
+// GIF frames are shown 4/100 s apart, 25 frames per second
+const GIF_DELAY: u16 = 4;
+
+// screens are as Machine::screen gives them: one bool per pixel, row by row, true is black
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Pbm,
+    Png,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "pbm" => Some(Format::Pbm),
+            "png" => Some(Format::Png),
+            _ => None,
+        }
+    }
+
+    pub fn encode(self, screen: &[bool]) -> Vec<u8> {
+        match self {
+            Format::Pbm => pbm(screen),
+            Format::Png => png(screen),
+        }
+    }
+}
+
+// 8 pixels per byte, leftmost in the highest bit
+fn pack(screen: &[bool], black: bool) -> Vec<u8> {
+    screen
+        .chunks(8)
+        .map(|pixels| pixels.iter().fold(0, |byte, pixel| (byte << 1) | (*pixel == black) as u8))
+        .collect()
+}
+
+// binary PBM (P4), where a set bit is black
+pub fn pbm(screen: &[bool]) -> Vec<u8> {
+    let mut data = format!("P4\n{} {}\n", WIDTH, HEIGHT).into_bytes();
+    data.extend(pack(screen, true));
+    data
+}
+
+// 1-bit grayscale PNG, where a set bit is white
+pub fn png(screen: &[bool]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, WIDTH as u32, HEIGHT as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::One);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&pack(screen, false)).unwrap();
+    writer.finish().unwrap();
+    data
+}
+
+// writes the screen as PBM or PNG, chosen by the extension of path
+pub fn save(path: &Path, screen: &[bool]) -> io::Result<()> {
+    let format = Format::from_path(path).ok_or_else(|| unsupported(path))?;
+    std::fs::write(path, format.encode(screen))
+}
+
+fn unsupported(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{}: use .pbm, .png or .gif", path.display()))
+}
+
+enum Output {
+    Gif(gif::Encoder<BufWriter<File>>),
+    // frame.png is written as frame-00000.png, frame-00001.png, ...
+    Images(PathBuf, Format),
+}
+
+// takes a frame every interval cycles, into an animated GIF or numbered PBM/PNG files
+pub struct Recorder {
+    interval: u64,
+    remaining: u64,
+    frames: usize,
+    output: Output,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, interval: u64) -> io::Result<Recorder> {
+        let output = if path.extension().and_then(|extension| extension.to_str()) == Some("gif") {
+            let file = BufWriter::new(File::create(path)?);
+            // palette index 0 is white, 1 is black
+            let mut encoder = gif::Encoder::new(file, WIDTH as u16, HEIGHT as u16, &[255, 255, 255, 0, 0, 0])
+                .map_err(io::Error::other)?;
+            encoder.set_repeat(gif::Repeat::Infinite).map_err(io::Error::other)?;
+            Output::Gif(encoder)
+        } else {
+            Output::Images(path.to_path_buf(), Format::from_path(path).ok_or_else(|| unsupported(path))?)
+        };
+        let interval = interval.max(1);
+        Ok(Recorder { interval, remaining: interval, frames: 0, output })
+    }
+
+    // counts one cycle; true when a frame is due
+    pub fn tick(&mut self) -> bool {
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.remaining = self.interval;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn record(&mut self, screen: &[bool]) -> io::Result<()> {
+        match &mut self.output {
+            Output::Gif(encoder) => {
+                let buffer: Vec<u8> = screen.iter().map(|black| *black as u8).collect();
+                let mut frame = gif::Frame::from_indexed_pixels(WIDTH as u16, HEIGHT as u16, buffer, None);
+                frame.delay = GIF_DELAY;
+                encoder.write_frame(&frame).map_err(io::Error::other)?;
+            },
+            Output::Images(path, format) => {
+                std::fs::write(numbered(path, self.frames), format.encode(screen))?;
+            },
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    // a GIF is only complete once its trailer is written
+    pub fn finish(self) -> io::Result<()> {
+        match self.output {
+            Output::Gif(encoder) => encoder.into_inner()?.flush(),
+            Output::Images(..) => Ok(()),
+        }
+    }
+}
+
+fn numbered(path: &Path, n: usize) -> PathBuf {
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("frame");
+    let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("");
+    path.with_file_name(format!("{}-{:05}.{}", stem, n, extension))
+}
+
+// the first of path-0.png, path-1.png, ... that does not exist yet
+pub fn unused_path(prefix: &str, extension: &str) -> PathBuf {
+    (0..)
+        .map(|n| PathBuf::from(format!("{}-{}.{}", prefix, n, extension)))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+
+    // a black pixel in the top left and bottom right corners
+    fn corners() -> Vec<bool> {
+        let mut screen = vec![false; WIDTH * HEIGHT];
+        screen[0] = true;
+        screen[WIDTH * HEIGHT - 1] = true;
+        screen
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hack_common_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[rstest]
+    #[case("out.pbm", Some(Format::Pbm))]
+    #[case("dir/out.png", Some(Format::Png))]
+    #[case("out.gif", None)]
+    #[case("out", None)]
+    fn test_format_from_path(#[case] input: &str, #[case] output: Option<Format>) {
+        assert_eq!(output, Format::from_path(Path::new(input)));
+    }
+
+    #[test]
+    fn test_pbm() {
+        let data = pbm(&corners());
+        let header = b"P4\n512 256\n";
+        assert_eq!(header.len() + WIDTH * HEIGHT / 8, data.len());
+        assert_eq!(&header[..], &data[..header.len()]);
+        assert_eq!(0b1000_0000, data[header.len()]);
+        assert_eq!(0b0000_0001, data[data.len() - 1]);
+        assert_eq!(2, data[header.len()..].iter().map(|byte| byte.count_ones()).sum::<u32>());
+    }
+
+    #[test]
+    fn test_png() {
+        let data = png(&corners());
+        let mut reader = png::Decoder::new(&data[..]).read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!((512, 256), (info.width, info.height));
+        assert_eq!((png::ColorType::Grayscale, png::BitDepth::One), (info.color_type, info.bit_depth));
+        assert_eq!(0b0111_1111, buffer[0]);
+        assert_eq!(0b1111_1110, buffer[info.buffer_size() - 1]);
+    }
+
+    #[test]
+    fn test_recorder_images() {
+        let dir = temp_dir("images");
+        let mut recorder = Recorder::create(&dir.join("frame.pbm"), 3).unwrap();
+        for _ in 0..7 {
+            if recorder.tick() {
+                recorder.record(&corners()).unwrap();
+            }
+        }
+        assert_eq!(2, recorder.frames());
+        recorder.finish().unwrap();
+        assert_eq!(pbm(&corners()), std::fs::read(dir.join("frame-00001.pbm")).unwrap());
+        assert!(!dir.join("frame-00002.pbm").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_recorder_gif() {
+        let dir = temp_dir("gif");
+        let path = dir.join("recording.gif");
+        let mut recorder = Recorder::create(&path, 1).unwrap();
+        recorder.record(&vec![false; WIDTH * HEIGHT]).unwrap();
+        recorder.record(&corners()).unwrap();
+        recorder.finish().unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push((frame.delay, frame.buffer.iter().filter(|index| **index == 1).count()));
+        }
+        assert_eq!(vec![(GIF_DELAY, 0), (GIF_DELAY, 2)], frames);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert!(save(Path::new("out.bmp"), &corners()).is_err());
+        assert!(Recorder::create(Path::new("out.bmp"), 1).is_err());
+    }
+}
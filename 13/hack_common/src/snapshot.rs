@@ -0,0 +1,208 @@
+use crate::machine::Machine;
+
+// the state of a machine, taken between cycles and restored into any backend;
+// the keyboard is not in it, KBD being whatever key is down
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub pc: u16,
+    pub a: u16,
+    pub d: u16,
+    // the program as it was loaded, zeros at its end included
+    pub rom: Vec<u16>,
+    // the RAM and screen up to KBD
+    pub ram: Vec<u16>,
+}
+
+const RAM_WORDS: usize = 24576;
+const HEADER: &str = "hack snapshot";
+
+impl Snapshot {
+    pub fn take(machine: &dyn Machine) -> Self {
+        Snapshot {
+            pc: machine.get_pc(),
+            a: machine.get_a(),
+            d: machine.get_d(),
+            rom: machine.get_rom_words(),
+            ram: machine.get_ram_words(),
+        }
+    }
+
+    pub fn restore(&self, machine: &mut dyn Machine) {
+        let rom: Vec<String> = self.rom.iter().map(|word| format!("{:016b}", word)).collect();
+        machine.load_program(rom.iter().map(String::as_str).collect());
+        let mut ram = self.ram.clone();
+        ram.resize(RAM_WORDS, 0);
+        machine.set_ram_words(&ram);
+        machine.set_registers(self.pc, self.a, self.d);
+    }
+
+    // words in hex; the RAM as runs of words that are not 0, each after its address
+    //   hack snapshot
+    //   registers <pc> <a> <d>
+    //   rom <word> <word> ...
+    //   ram <address> <word> <word> ...
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\nregisters {} {} {}\nrom", HEADER, self.pc, self.a, self.d);
+        for word in &self.rom {
+            text += &format!(" {:04x}", word);
+        }
+        text += "\n";
+        let mut address = 0;
+        while address < self.ram.len() {
+            if self.ram[address] == 0 {
+                address += 1;
+                continue;
+            }
+            text += &format!("ram {}", address);
+            while address < self.ram.len() && self.ram[address] != 0 {
+                text += &format!(" {:04x}", self.ram[address]);
+                address += 1;
+            }
+            text += "\n";
+        }
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate();
+        if lines.next().map(|(_, line)| line.trim()) != Some(HEADER) {
+            return Err("not a snapshot".to_string());
+        }
+        let mut snapshot = Snapshot {
+            pc: 0,
+            a: 0,
+            d: 0,
+            rom: Vec::new(),
+            ram: vec![0; RAM_WORDS],
+        };
+        for (i, line) in lines {
+            let mut fields = line.split_whitespace();
+            let error = |message: &str| format!("line {}: {}", i + 1, message);
+            match fields.next() {
+                None => {},
+                Some("registers") => {
+                    let registers = fields
+                        .map(|field| field.parse::<u16>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| error("invalid register"))?;
+                    match registers[..] {
+                        [pc, ..] if pc > 32767 => return Err(error("pc past the ROM")),
+                        [pc, a, d] => (snapshot.pc, snapshot.a, snapshot.d) = (pc, a, d),
+                        _ => return Err(error("expected pc, a and d")),
+                    }
+                },
+                Some("rom") => {
+                    snapshot.rom = fields.map(parse_word).collect::<Option<_>>().ok_or_else(|| error("invalid word"))?;
+                    if snapshot.rom.len() > 32768 {
+                        return Err(error("more than 32768 words"));
+                    }
+                },
+                Some("ram") => {
+                    let address = fields
+                        .next()
+                        .and_then(|field| field.parse::<usize>().ok())
+                        .ok_or_else(|| error("invalid address"))?;
+                    let words: Vec<u16> = fields.map(parse_word).collect::<Option<_>>().ok_or_else(|| error("invalid word"))?;
+                    let end = address
+                        .checked_add(words.len())
+                        .filter(|end| address < RAM_WORDS && *end <= RAM_WORDS)
+                        .ok_or_else(|| error("past the screen"))?;
+                    snapshot.ram[address..end].copy_from_slice(&words);
+                },
+                Some(field) => return Err(error(&format!("unknown {}", field))),
+            }
+        }
+        if snapshot.rom.is_empty() {
+            return Err("no program".to_string());
+        }
+        Ok(snapshot)
+    }
+}
+
+fn parse_word(field: &str) -> Option<u16> {
+    u16::from_str_radix(field, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+    use crate::machine::Backend;
+
+    // @17, D=A, @3, M=D, @16384, M=D
+    const STORE_17: [&str; 6] = [
+        "0000000000010001", "1110110000010000", "0000000000000011", "1110001100001000",
+        "0100000000000000", "1110001100001000",
+    ];
+
+    #[rstest]
+    #[case(Backend::Emulator, Backend::BuiltIn)]
+    #[case(Backend::BuiltIn, Backend::Gates)]
+    #[case(Backend::Gates, Backend::Emulator)]
+    fn test_take_and_restore(#[case] from: Backend, #[case] to: Backend) {
+        let mut machine = from.create();
+        machine.load_program(STORE_17.to_vec());
+        machine.step(true, 0);
+        for _ in 0..4 {
+            machine.step(false, 0);
+        }
+        let snapshot = Snapshot::take(machine.as_ref());
+        assert_eq!((4, 3, 17), (snapshot.pc, snapshot.a, snapshot.d));
+        assert_eq!(6, snapshot.rom.len());
+        assert_eq!(17, snapshot.ram[3]);
+
+        let mut restored = to.create();
+        Snapshot::from_text(&snapshot.to_text()).unwrap().restore(restored.as_mut());
+        assert_eq!(snapshot, Snapshot::take(restored.as_ref()));
+        machine.step(false, 0);
+        machine.step(false, 0);
+        restored.step(false, 0);
+        restored.step(false, 0);
+        assert_eq!(
+            (6, 16384, 17, 17),
+            (restored.get_pc(), restored.get_a(), restored.get_d(), restored.get_ram(16384))
+        );
+        assert_eq!(machine.screen(), restored.screen());
+    }
+
+    #[rstest]
+    #[case(Backend::Emulator)]
+    #[case(Backend::BuiltIn)]
+    #[case(Backend::Gates)]
+    fn test_take_zeros_at_the_end(#[case] backend: Backend) {
+        let mut machine = backend.create();
+        // @17, D=A, then two words of 0 that are part of the program
+        machine.load_program(vec!["0000000000010001", "1110110000010000", "0000000000000000", "0000000000000000"]);
+        let snapshot = Snapshot::take(machine.as_ref());
+        assert_eq!(vec![0x11, 0xec10, 0, 0], snapshot.rom);
+        assert_eq!(Ok(snapshot.clone()), Snapshot::from_text(&snapshot.to_text()));
+    }
+
+    #[test]
+    fn test_to_text() {
+        let mut ram = vec![0; RAM_WORDS];
+        ram[1] = 0xffff;
+        ram[2] = 2;
+        ram[24575] = 1;
+        let snapshot = Snapshot { pc: 1, a: 2, d: 3, rom: vec![0x11, 0xec10], ram };
+        assert_eq!("hack snapshot\nregisters 1 2 3\nrom 0011 ec10\nram 1 ffff 0002\nram 24575 0001\n", snapshot.to_text());
+    }
+
+    #[rstest]
+    #[case("", "not a snapshot")]
+    #[case("hack snapshot\nregisters 0 0 0", "no program")]
+    #[case("hack snapshot\nregisters 0 0 0\nrom", "no program")]
+    #[case("hack snapshot\nregisters 32768 0 0\nrom 0011", "line 2: pc past the ROM")]
+    #[case("hack snapshot\nregisters 1 2", "line 2: expected pc, a and d")]
+    #[case("hack snapshot\nregisters 1 2 x", "line 2: invalid register")]
+    #[case("hack snapshot\nrom 0011 xyz", "line 2: invalid word")]
+    #[case("hack snapshot\n\nram 24575 1 2", "line 3: past the screen")]
+    #[case("hack snapshot\nram 24576", "line 2: past the screen")]
+    #[case("hack snapshot\nram 18446744073709551615 1", "line 2: past the screen")]
+    #[case("hack snapshot\nram 0 xyz", "line 2: invalid word")]
+    #[case("hack snapshot\nram", "line 2: invalid address")]
+    #[case("hack snapshot\nkbd 1", "line 2: unknown kbd")]
+    fn test_from_text_errors(#[case] input: &str, #[case] output: &str) {
+        assert_eq!(Err(output.to_string()), Snapshot::from_text(input));
+    }
+}
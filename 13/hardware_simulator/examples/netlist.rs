@@ -0,0 +1,30 @@
+use hardware_simulator::netlist;
+
+// cargo run --example netlist -- ALU dot | dot -Tsvg > alu.svg
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let chip = args.get(1).map(|s| s.as_str()).unwrap_or("ALU");
+    let format = args.get(2).map(|s| s.as_str()).unwrap_or("dot");
+
+    let netlist = match netlist::get(chip) {
+        Some(netlist) => netlist,
+        None => {
+            eprintln!("error: no netlist for chip {} (ALU, CPU, Memory, Computer)", chip);
+            std::process::exit(1);
+        }
+    };
+    match format {
+        "dot" => match netlist.to_dot() {
+            Ok(dot) => print!("{}", dot),
+            Err(err) => {
+                eprintln!("error: {}", err);
+                std::process::exit(1);
+            }
+        },
+        "json" => println!("{}", netlist.to_json()),
+        _ => {
+            eprintln!("error: unknown format {} (dot, json)", format);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use hardware_simulator::truth_table;
+
+// cargo run --example truth_table -- Xor
+// cargo run --example truth_table -- ALU ../../02/ALU.cmp
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let name = args.get(1).map(|s| s.as_str()).unwrap_or("Xor");
+
+    let chip = match truth_table::chip(name) {
+        Some(chip) => chip,
+        None => {
+            let names: Vec<&str> = truth_table::CHIPS.iter().map(|chip| chip.name).collect();
+            eprintln!("error: unknown chip {} ({})", name, names.join(", "));
+            std::process::exit(1);
+        }
+    };
+    let path = match args.get(2) {
+        Some(path) => path,
+        None => {
+            print!("{}", truth_table::generate(chip));
+            return;
+        }
+    };
+
+    let cmp = match std::fs::read_to_string(path) {
+        Ok(cmp) => cmp,
+        Err(e) => {
+            eprintln!("error: cannot read {}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+    match truth_table::compare(chip, &cmp) {
+        Ok(mismatches) if mismatches.is_empty() => println!("{}: ok", path),
+        Ok(mismatches) => {
+            for mismatch in &mismatches {
+                println!("{}", mismatch);
+            }
+            eprintln!("{}: {} mismatched rows", path, mismatches.len());
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
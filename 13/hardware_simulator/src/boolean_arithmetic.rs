@@ -77,6 +77,27 @@ pub fn inc16(input: Word) -> Word {
     add16(input, u16_to_word(0b0000_0000_0000_0001))
 }
 
+// signals inside the ALU, e.g. for D-M: x=D, y=M, nx_out=!D, f_out=!D+M, out=!(!D+M)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AluTrace {
+    pub x: u16,
+    pub y: u16,
+    pub zx: Binary,
+    pub nx: Binary,
+    pub zy: Binary,
+    pub ny: Binary,
+    pub f: Binary,
+    pub no: Binary,
+    pub zx_out: u16,
+    pub nx_out: u16,
+    pub zy_out: u16,
+    pub ny_out: u16,
+    pub f_out: u16,
+    pub out: u16,
+    pub zr: Binary,
+    pub ng: Binary,
+}
+
 pub fn alu(
     x: Word,
     y: Word, 
@@ -87,6 +108,21 @@ pub fn alu(
     f: Binary, 
     no: Binary
 ) -> (Word, Binary, Binary) {
+    let (output, zr, ng, _) = alu_traced(x, y, zx, nx, zy, ny, f, no);
+    (output, zr, ng)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn alu_traced(
+    x: Word,
+    y: Word, 
+    zx: Binary, 
+    nx: Binary, 
+    zy: Binary, 
+    ny: Binary, 
+    f: Binary, 
+    no: Binary
+) -> (Word, Binary, Binary, AluTrace) {
     let x1 = mux16(x, u16_to_word(0b0000_0000_0000_0000), zx);
     let x2 = mux16(x1, not16(x1), nx);
     let y1 = mux16(y, u16_to_word(0b0000_0000_0000_0000), zy);
@@ -99,10 +135,19 @@ pub fn alu(
     let output2 = [output[8], output[9], output[10], output[11], output[12], output[13], output[14], output[15]];
     let zr = not(or(or8way(output1), or8way(output2)));
     let ng = output[15];
-    (output, zr, ng)
+    let trace = AluTrace {
+        x: word_to_u16(x), y: word_to_u16(y),
+        zx, nx, zy, ny, f, no,
+        zx_out: word_to_u16(x1), nx_out: word_to_u16(x2),
+        zy_out: word_to_u16(y1), ny_out: word_to_u16(y2),
+        f_out: word_to_u16(xy), out: word_to_u16(output),
+        zr, ng
+    };
+    (output, zr, ng, trace)
 }
 
-pub fn alu_built_in(
+#[allow(clippy::too_many_arguments)]
+pub fn alu_built_in_traced(
     x: Word,
     y: Word, 
     zx: Binary, 
@@ -111,7 +156,7 @@ pub fn alu_built_in(
     ny: Binary, 
     f: Binary, 
     no: Binary
-) -> (Word, Binary, Binary) {
+) -> (Word, Binary, Binary, AluTrace) {
     let x1 = mux16_built_in(x, u16_to_word(0b0000_0000_0000_0000), zx);
     let x2 = mux16_built_in(x1, not16(x1), nx);
     let y1 = mux16_built_in(y, u16_to_word(0b0000_0000_0000_0000), zy);
@@ -124,7 +169,15 @@ pub fn alu_built_in(
     let output2 = [output[8], output[9], output[10], output[11], output[12], output[13], output[14], output[15]];
     let zr = !(or8way_built_in(output1) || or8way_built_in(output2));
     let ng = output[15];
-    (output, zr, ng)
+    let trace = AluTrace {
+        x: word_to_u16(x), y: word_to_u16(y),
+        zx, nx, zy, ny, f, no,
+        zx_out: word_to_u16(x1), nx_out: word_to_u16(x2),
+        zy_out: word_to_u16(y1), ny_out: word_to_u16(y2),
+        f_out: word_to_u16(xy), out: word_to_u16(output),
+        zr, ng
+    };
+    (output, zr, ng, trace)
 }
 
 #[cfg(test)]
@@ -234,4 +287,40 @@ mod tests {
         let (o, zr, ng) = (u16_to_word(o), u8_to_1bit(zr), u8_to_1bit(ng));
         assert_eq!((o, zr, ng), alu(x, y, zx, nx, zy, ny, f, no));
     }
+
+    #[rstest]
+    // D-M with D=17, M=3: x=17, !x, !x+y, !(!x+y) = x-y
+    #[case((0b0000_0000_0001_0001, 0b0000_0000_0000_0011, 0, 1, 0, 0, 1, 1), (
+        0b0000_0000_0001_0001, 0b1111_1111_1110_1110,
+        0b0000_0000_0000_0011, 0b0000_0000_0000_0011,
+        0b1111_1111_1111_0001, 0b0000_0000_0000_1110
+    ))]
+    // D&A with zx/nx/zy/ny all off
+    #[case((0b0000_0000_0001_0001, 0b0000_0000_0000_0011, 0, 0, 0, 0, 0, 0), (
+        0b0000_0000_0001_0001, 0b0000_0000_0001_0001,
+        0b0000_0000_0000_0011, 0b0000_0000_0000_0011,
+        0b0000_0000_0000_0001, 0b0000_0000_0000_0001
+    ))]
+    // -1: zx, nx, zy, f
+    #[case((0b0000_0000_0001_0001, 0b0000_0000_0000_0011, 1, 1, 1, 0, 1, 0), (
+        0b0000_0000_0000_0000, 0b1111_1111_1111_1111,
+        0b0000_0000_0000_0000, 0b0000_0000_0000_0000,
+        0b1111_1111_1111_1111, 0b1111_1111_1111_1111
+    ))]
+    fn test_alu_traced(#[case] input: (u16, u16, u8, u8, u8, u8, u8, u8), #[case] output: (u16, u16, u16, u16, u16, u16)) {
+        let (x, y, zx, nx, zy, ny, f, no) = input;
+        let (zx_out, nx_out, zy_out, ny_out, f_out, out) = output;
+        let (zx, nx, zy, ny, f, no) = (
+            u8_to_1bit(zx), u8_to_1bit(nx), u8_to_1bit(zy), u8_to_1bit(ny), u8_to_1bit(f), u8_to_1bit(no)
+        );
+        let trace = AluTrace {
+            x, y, zx, nx, zy, ny, f, no,
+            zx_out, nx_out, zy_out, ny_out, f_out, out,
+            zr: out == 0, ng: out >= 0b1000_0000_0000_0000
+        };
+        let (o, zr, ng, t) = alu_traced(u16_to_word(x), u16_to_word(y), zx, nx, zy, ny, f, no);
+        assert_eq!((u16_to_word(out), trace.zr, trace.ng, trace), (o, zr, ng, t));
+        let (o, zr, ng, t) = alu_built_in_traced(u16_to_word(x), u16_to_word(y), zx, nx, zy, ny, f, no);
+        assert_eq!((u16_to_word(out), trace.zr, trace.ng, trace), (o, zr, ng, t));
+    }
 }
@@ -71,8 +71,7 @@ pub fn and16_built_in(a: Word, b: Word) -> Word {
     word
 }
 
-#[allow(dead_code)]
-fn or16(a: Word, b: Word) -> Word {
+pub fn or16(a: Word, b: Word) -> Word {
     let mut word = u16_to_word(0b0000_0000_0000_0000);
     for i in 0..16 {
         word[i] = or(a[i],  b[i]);
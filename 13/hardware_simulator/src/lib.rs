@@ -3,8 +3,13 @@ mod boolean_logic;
 mod helper;
 mod boolean_arithmetic;
 mod sequential_circuit;
+pub mod netlist;
+pub mod truth_table;
 
 pub use boolean_logic::Binary;
+pub use boolean_arithmetic::AluTrace;
+// the same reset modes as the emulator, so a frontend switches backends without mapping them
+pub use cpu_emulator::ResetMode;
 use boolean_logic::*;
 use helper::*;
 use boolean_arithmetic::*;
@@ -13,7 +18,8 @@ use sequential_circuit::*;
 #[derive(Clone)]
 pub struct Screen {
     rams: Vec<RAM4K>,
-    screen: [Binary; 131072],
+    // a pixel per element, row by row; on the heap, as 128KB is too much for a wasm stack
+    screen: Vec<Binary>,
 }
 
 impl Screen {
@@ -22,7 +28,7 @@ impl Screen {
         for _ in 0..2 {
             rams.push(RAM4K::new());
         }
-        Screen { rams, screen: [false; 131072] }
+        Screen { rams, screen: vec![false; 131072] }
     }
 
     fn update(&mut self, clk: Binary, input: Word, load: Binary, address: [Binary; 13]) {
@@ -51,7 +57,7 @@ impl Screen {
         )
     }
 
-    pub fn get_all(&self) -> [Binary; 131072] {
+    pub fn screen(&self) -> &[Binary] {
         // let mut screen = [false; 131072];
         // let mut x = 0;
         // for i in 0..8192 {
@@ -63,19 +69,34 @@ impl Screen {
         //     }
         // }
         // screen
-        self.screen
+        &self.screen
+    }
+
+    fn words(&self) -> Vec<u16> {
+        self.rams.iter().flat_map(RAM4K::words).collect()
+    }
+
+    fn set_words(&mut self, words: &[u16]) {
+        for (ram, words) in self.rams.iter_mut().zip(words.chunks(4096)) {
+            ram.set_words(words);
+        }
+        for (pixels, word) in self.screen.chunks_mut(16).zip(words) {
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                *pixel = word >> i & 1 != 0;
+            }
+        }
     }
 }
 
 #[derive(Clone)]
 pub struct ScreenBuiltIn {
-    screen: [Binary; 131072],
+    screen: Vec<Binary>,
 }
 
 impl ScreenBuiltIn {
     pub fn new() -> Self {
         ScreenBuiltIn { 
-            screen: [false; 131072],
+            screen: vec![false; 131072],
         }
     }
 
@@ -101,8 +122,23 @@ impl ScreenBuiltIn {
         word
     }
 
-    pub fn get_all(&self) -> [Binary; 131072] {
+    pub fn screen(&self) -> &[Binary] {
+        &self.screen
+    }
+
+    fn words(&self) -> Vec<u16> {
         self.screen
+            .chunks(16)
+            .map(|pixels| pixels.iter().rev().fold(0, |word, pixel| word << 1 | *pixel as u16))
+            .collect()
+    }
+
+    fn set_words(&mut self, words: &[u16]) {
+        for (pixels, word) in self.screen.chunks_mut(16).zip(words) {
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                *pixel = word >> i & 1 != 0;
+            }
+        }
     }
 }
 
@@ -174,8 +210,21 @@ impl Memory {
         mux4way16(ram_output, ram_output, screen_output, keyboard_output, [address[13], address[14]])
     }
 
-    pub fn get_screen(&self) -> [Binary; 131072] {
-        self.screen.get_all()
+    pub fn screen(&self) -> &[Binary] {
+        self.screen.screen()
+    }
+
+    // RAM and screen, up to KBD
+    fn words(&self) -> Vec<u16> {
+        let mut words = self.ram.words();
+        words.extend(self.screen.words());
+        words
+    }
+
+    fn set_words(&mut self, words: &[u16]) {
+        let (ram, screen) = words.split_at(words.len().min(16384));
+        self.ram.set_words(ram);
+        self.screen.set_words(screen);
     }
 }
 
@@ -209,18 +258,54 @@ impl MemoryBuiltIn {
         mux4way16_built_in(ram_output, ram_output, screen_output, keyboard_output, [address[13], address[14]])
     }
 
-    pub fn get_screen(&self) -> [Binary; 131072] {
-        self.screen.get_all()
+    pub fn screen(&self) -> &[Binary] {
+        self.screen.screen()
+    }
+
+    // RAM and screen, up to KBD
+    fn words(&self) -> Vec<u16> {
+        let mut words = self.ram.words();
+        words.extend(self.screen.words());
+        words
+    }
+
+    fn set_words(&mut self, words: &[u16]) {
+        let (ram, screen) = words.split_at(words.len().min(16384));
+        self.ram.set_words(ram);
+        self.screen.set_words(screen);
     }
 }
 
+// signals of one CPU cycle, taken from the clk=true phase where the registers latch
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CpuTrace {
+    pub pc: u16,
+    pub instruction: u16,
+    pub reset: Binary,
+    pub a: u16,
+    pub d: u16,
+    pub in_m: u16,
+    pub c_instruction: Binary,
+    pub y_is_m: Binary,
+    pub in_a: u16,
+    pub write_a: Binary,
+    pub write_d: Binary,
+    pub write_m: Binary,
+    pub out_m: u16,
+    pub address_m: u16,
+    pub jump: Binary,
+    pub write_pc: Binary,
+    pub alu: AluTrace,
+}
+
 #[derive(Debug, Clone)]
 pub struct CPU {
     a_register: Register,
     d_register: Register,
     out_m: Word,
     write_m: Binary,
-    pc: PC
+    pc: PC,
+    trace: CpuTrace
 }
 
 impl CPU {
@@ -230,7 +315,8 @@ impl CPU {
             d_register: Register::new(),
             out_m: [false; 16],
             write_m: false,
-            pc: PC::new()
+            pc: PC::new(),
+            trace: CpuTrace::default()
         }
     }
 
@@ -240,7 +326,7 @@ impl CPU {
         let out_d = self.d_register.get(false);
         let out_a = self.a_register.get(false);
         let out_a_or_m = mux16(out_a, in_m, instruction[12]);
-        let (out_m, out_zr, out_ng) = alu(
+        let (out_m, out_zr, out_ng, alu_trace) = alu_traced(
             out_d,  /* x */
             out_a_or_m, /* y */
             instruction[11], /* zx */
@@ -268,6 +354,27 @@ impl CPU {
         let w3 = or(w0, w1);
         let out_jump = or(w3, w2);
         let write_pc = and(instruction[15], out_jump);
+        if clk {
+            self.trace = CpuTrace {
+                pc: word_to_u16(self.pc.get(false)),
+                instruction: word_to_u16(instruction),
+                reset,
+                a: word_to_u16(out_a),
+                d: word_to_u16(out_d),
+                in_m: word_to_u16(in_m),
+                c_instruction: instruction[15],
+                y_is_m: instruction[12],
+                in_a: word_to_u16(in_a),
+                write_a,
+                write_d,
+                write_m: self.write_m,
+                out_m: word_to_u16(out_m),
+                address_m: word_to_u16(out_a) & 0b0111_1111_1111_1111,
+                jump: out_jump,
+                write_pc,
+                alu: alu_trace,
+            };
+        }
         self.pc.update(clk, out_a, write_pc, true, reset);
     }
 
@@ -277,6 +384,20 @@ impl CPU {
         let address_m = word_to_bit15(out_a);
         (self.out_m, self.write_m, pc, address_m)
     }
+
+    pub fn get_trace(&self) -> CpuTrace {
+        self.trace
+    }
+
+    pub fn get_alu_trace(&self) -> AluTrace {
+        self.trace.alu
+    }
+
+    fn set_registers(&mut self, pc: u16, a: u16, d: u16) {
+        self.pc.set(u16_to_word(pc));
+        self.a_register.set(u16_to_word(a));
+        self.d_register.set(u16_to_word(d));
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -285,7 +406,8 @@ pub struct CPUBuiltIn {
     d_register: Register,
     out_m: Word,
     write_m: Binary,
-    pc: PC
+    pc: PC,
+    trace: CpuTrace
 }
 
 impl CPUBuiltIn {
@@ -295,7 +417,8 @@ impl CPUBuiltIn {
             d_register: Register::new(),
             out_m: [false; 16],
             write_m: false,
-            pc: PC::new()
+            pc: PC::new(),
+            trace: CpuTrace::default()
         }
     }
 
@@ -305,7 +428,7 @@ impl CPUBuiltIn {
         let out_d = self.d_register.get(false);
         let out_a = self.a_register.get(false);
         let out_a_or_m = mux16_built_in(out_a, in_m, instruction[12]);
-        let (out_m, out_zr, out_ng) = alu_built_in(
+        let (out_m, out_zr, out_ng, alu_trace) = alu_built_in_traced(
             out_d,  /* x */
             out_a_or_m, /* y */
             instruction[11], /* zx */
@@ -334,6 +457,27 @@ impl CPUBuiltIn {
         let w3 = w0 || w1;
         let out_jump = w3 || w2;
         let write_pc = instruction[15] && out_jump;
+        if clk {
+            self.trace = CpuTrace {
+                pc: word_to_u16(self.pc.get(false)),
+                instruction: word_to_u16(instruction),
+                reset,
+                a: word_to_u16(out_a),
+                d: word_to_u16(out_d),
+                in_m: word_to_u16(in_m),
+                c_instruction: instruction[15],
+                y_is_m: instruction[12],
+                in_a: word_to_u16(in_a),
+                write_a,
+                write_d,
+                write_m: self.write_m,
+                out_m: word_to_u16(out_m),
+                address_m: word_to_u16(out_a) & 0b0111_1111_1111_1111,
+                jump: out_jump,
+                write_pc,
+                alu: alu_trace,
+            };
+        }
         self.pc.update(clk, out_a, write_pc, true, reset);
     }
 
@@ -343,6 +487,31 @@ impl CPUBuiltIn {
         let address_m = word_to_bit15(out_a);
         (self.out_m, self.write_m, pc, address_m)
     }
+
+    pub fn get_trace(&self) -> CpuTrace {
+        self.trace
+    }
+
+    pub fn get_alu_trace(&self) -> AluTrace {
+        self.trace.alu
+    }
+
+    fn set_registers(&mut self, pc: u16, a: u16, d: u16) {
+        self.pc.set(u16_to_word(pc));
+        self.a_register.update(true, u16_to_word(a), true);
+        self.d_register.update(true, u16_to_word(d), true);
+    }
+}
+
+// the 32K words of a ROM holding the instructions in .hack text, 0 after them
+fn rom_words(instructions: Vec<&str>) -> Vec<u16> {
+    let mut words: Vec<u16> = instructions
+        .iter()
+        .take(32768)
+        .map(|instruction| instruction.chars().take(16).enumerate().fold(0, |word, (i, c)| word | ((c == '1') as u16) << (15 - i)))
+        .collect();
+    words.resize(32768, 0);
+    words
 }
 
 #[derive(Clone)]
@@ -389,22 +558,16 @@ impl ROM32K {
         )
     }
 
+    // stored without a clock, which a ROM never gets while the computer runs
     pub fn load(&mut self, instructions: Vec<&str>) {
-        let mut counter = u16_to_word(0b0000000000000000);
-        for instruction in instructions {
-            let mut decorded_instruction = u16_to_word(0b0000000000000000);
-            for (i, c) in instruction.chars().enumerate() {
-                if c == '1' {
-                    decorded_instruction[15 - i] = true;
-                }
-            }
-            // println!("instruction: {}", word_to_u16(decorded_instruction));
-    
-            let address = word_to_bit15(counter);
-            self.update(true, decorded_instruction, address);
-            counter = add16(counter, u16_to_word(0b0000000000000001));
+        for (ram, words) in self.rams.iter_mut().zip(rom_words(instructions).chunks(4096)) {
+            ram.set_words(words);
         }
     }
+
+    fn words(&self) -> Vec<u16> {
+        self.rams.iter().flat_map(|ram| ram.words()).collect()
+    }
 }
 
 #[derive(Clone)]
@@ -452,20 +615,81 @@ impl ROM32KBuiltIn {
     }
 
     pub fn load(&mut self, instructions: Vec<&str>) {
-        let mut counter = u16_to_word(0b0000000000000000);
-        for instruction in instructions {
-            let mut decorded_instruction = u16_to_word(0b0000000000000000);
-            for (i, c) in instruction.chars().enumerate() {
-                if c == '1' {
-                    decorded_instruction[15 - i] = true;
-                }
-            }
-    
-            let address = word_to_bit15(counter);
-            self.update(true, decorded_instruction, address);
-            counter = add16_built_in(counter, u16_to_word(0b0000000000000001));
+        for (ram, words) in self.rams.iter_mut().zip(rom_words(instructions).chunks(4096)) {
+            ram.set_words(words);
         }
     }
+
+    fn words(&self) -> Vec<u16> {
+        self.rams.iter().flat_map(|ram| ram.words()).collect()
+    }
+}
+
+// the registers and memory as a debugger or a snapshot sees them, the same for Computer and ComputerBuiltIn,
+// whose ROM, CPU and memory have the same methods
+macro_rules! computer_state {
+    () => {
+        // the address of the next instruction
+        pub fn get_pc(&self) -> u16 {
+            bit15_to_u16(self.pc_address)
+        }
+
+        pub fn get_a(&self) -> u16 {
+            word_to_u16(self.cpu.a_register.get(false))
+        }
+
+        pub fn get_d(&self) -> u16 {
+            word_to_u16(self.cpu.d_register.get(false))
+        }
+
+        pub fn get_ram(&self, address: u16) -> u16 {
+            word_to_u16(self.memory.get(false, word_to_bit15(u16_to_word(address))))
+        }
+
+        pub fn get_rom(&self, address: u16) -> u16 {
+            word_to_u16(self.rom.get(false, word_to_bit15(u16_to_word(address))))
+        }
+
+        // clocks a word into the RAM or the screen, as a debugger edits memory; the keyboard keeps its key
+        pub fn set_ram(&mut self, address: u16, value: u16) {
+            if address >= 24576 /* KBD */ {
+                return;
+            }
+            let key_code = self.memory.get(false, word_to_bit15(u16_to_word(24576 /* KBD */)));
+            for clk in [true, false] {
+                self.memory.update(clk, u16_to_word(value), true, word_to_bit15(u16_to_word(address)), key_code);
+            }
+            self.in_m = self.memory.get(false, word_to_bit15(self.cpu.a_register.get(false)));
+        }
+
+        // address and value of the RAM word written by the last step
+        pub fn get_last_write(&self) -> Option<(u16, u16)> {
+            let trace = self.cpu.get_trace();
+            if trace.write_m { Some((trace.address_m, trace.out_m)) } else { None }
+        }
+
+        // the RAM and screen up to KBD, for snapshots
+        pub fn get_ram_words(&self) -> Vec<u16> {
+            self.memory.words()
+        }
+
+        pub fn set_ram_words(&mut self, words: &[u16]) {
+            self.memory.set_words(words);
+            self.in_m = self.memory.get(false, word_to_bit15(self.cpu.a_register.get(false)));
+        }
+
+        // the loaded program, as many words as load_program was given, zeros at its end included
+        pub fn get_rom_words(&self) -> Vec<u16> {
+            self.rom.words()[..self.program_len].to_vec()
+        }
+
+        // where a snapshot was taken, the PC being the next instruction
+        pub fn set_registers(&mut self, pc: u16, a: u16, d: u16) {
+            self.cpu.set_registers(pc, a, d);
+            self.pc_address = word_to_bit15(u16_to_word(pc));
+            self.in_m = self.memory.get(false, word_to_bit15(u16_to_word(a)));
+        }
+    };
 }
 
 #[derive(Clone)]
@@ -474,7 +698,10 @@ pub struct Computer {
     cpu: CPU,
     memory: Memory,
     in_m: Word,
-    pc_address: [Binary; 15]
+    pc_address: [Binary; 15],
+    reset_mode: ResetMode,
+    // the number of words load_program wrote
+    program_len: usize
 }
 
 impl Computer {
@@ -484,11 +711,25 @@ impl Computer {
             cpu: CPU::new(),
             memory: Memory::new(),
             in_m: [false; 16],
-            pc_address: [false; 15]
+            pc_address: [false; 15],
+            reset_mode: ResetMode::default(),
+            program_len: 0
         }
     }
 
+    pub fn set_reset_mode(&mut self, reset_mode: ResetMode) {
+        self.reset_mode = reset_mode;
+    }
+
+    fn power_cycle(&mut self) {
+        self.cpu = CPU::new();
+        self.memory = Memory::new();
+        self.in_m = [false; 16];
+        self.pc_address = [false; 15];
+    }
+
     pub fn load_program(&mut self, instructions: Vec<&str>) {
+        self.program_len = instructions.len().min(32768);
         self.rom.load(instructions);
     }
 
@@ -504,6 +745,9 @@ impl Computer {
     }
 
     pub fn step(&mut self, reset: Binary, word: u16) {
+        if reset && self.reset_mode == ResetMode::PowerCycle {
+            self.power_cycle();
+        }
         let word = u16_to_word(word);
         let mut clk = true;
         self.update(clk, reset, word);
@@ -511,9 +755,20 @@ impl Computer {
         self.update(clk, reset, word);
     }
 
-    pub fn get_screen(&self) -> [Binary; 131072] {
-        self.memory.get_screen()
+    // a pixel per element, row by row, true is black
+    pub fn screen(&self) -> &[Binary] {
+        self.memory.screen()
     }
+
+    pub fn get_alu_trace(&self) -> AluTrace {
+        self.cpu.get_alu_trace()
+    }
+
+    pub fn get_cpu_trace(&self) -> CpuTrace {
+        self.cpu.get_trace()
+    }
+
+    computer_state!();
 }
 
 #[derive(Clone)]
@@ -522,7 +777,10 @@ pub struct ComputerBuiltIn {
     cpu: CPUBuiltIn,
     memory: MemoryBuiltIn,
     in_m: Word,
-    pc_address: [Binary; 15]
+    pc_address: [Binary; 15],
+    reset_mode: ResetMode,
+    // the number of words load_program wrote
+    program_len: usize
 }
 
 impl ComputerBuiltIn {
@@ -532,11 +790,25 @@ impl ComputerBuiltIn {
             cpu: CPUBuiltIn::new(),
             memory: MemoryBuiltIn::new(),
             in_m: [false; 16],
-            pc_address: [false; 15]
+            pc_address: [false; 15],
+            reset_mode: ResetMode::default(),
+            program_len: 0
         }
     }
 
+    pub fn set_reset_mode(&mut self, reset_mode: ResetMode) {
+        self.reset_mode = reset_mode;
+    }
+
+    fn power_cycle(&mut self) {
+        self.cpu = CPUBuiltIn::new();
+        self.memory = MemoryBuiltIn::new();
+        self.in_m = [false; 16];
+        self.pc_address = [false; 15];
+    }
+
     pub fn load_program(&mut self, instructions: Vec<&str>) {
+        self.program_len = instructions.len().min(32768);
         self.rom.load(instructions);
     }
 
@@ -552,6 +824,9 @@ impl ComputerBuiltIn {
     }
 
     pub fn step(&mut self, reset: Binary, word: u16) {
+        if reset && self.reset_mode == ResetMode::PowerCycle {
+            self.power_cycle();
+        }
         let word = u16_to_word(word);
         let mut clk = true;
         self.update(clk, reset, word);
@@ -559,14 +834,26 @@ impl ComputerBuiltIn {
         self.update(clk, reset, word);
     }
 
-    pub fn get_screen(&self) -> [Binary; 131072] {
-        self.memory.get_screen()
+    // a pixel per element, row by row, true is black
+    pub fn screen(&self) -> &[Binary] {
+        self.memory.screen()
+    }
+
+    pub fn get_alu_trace(&self) -> AluTrace {
+        self.cpu.get_alu_trace()
+    }
+
+    pub fn get_cpu_trace(&self) -> CpuTrace {
+        self.cpu.get_trace()
     }
+
+    computer_state!();
 }
 
 
 #[cfg(test)]
 mod tests {
+    use rstest::*;
     use super::*;
 
     #[test]
@@ -2063,8 +2350,244 @@ mod tests {
         assert_eq!(u16_to_word(0b0100_0000_0000_0000), output);
     }
 
+    #[test]
+    fn test_rom32k_last_word() {
+        let mut rom = ROM32K::new();
+        rom.load(vec!["0000000000000010", "1110110000010000", "0000000000010001"]);
+
+        // the last word loaded reads as itself on both clk, without another clock
+        let address = word_to_bit15(u16_to_word(2));
+        assert_eq!(u16_to_word(0b0000_0000_0001_0001), rom.get(true, address));
+        assert_eq!(u16_to_word(0b0000_0000_0001_0001), rom.get(false, address));
+    }
+
+    // a shorter program loaded after a longer one leaves no word of it
+    #[test]
+    fn test_rom32k_reload() {
+        let longer = vec!["0000000000000010", "1110110000010000", "0000000000010001"];
+        let shorter = vec!["0000000000000111"];
+        let (address_0, address_2) = (word_to_bit15(u16_to_word(0)), word_to_bit15(u16_to_word(2)));
+
+        let mut rom = ROM32K::new();
+        rom.load(longer.clone());
+        rom.load(shorter.clone());
+        assert_eq!((7, 0), (word_to_u16(rom.get(true, address_0)), word_to_u16(rom.get(true, address_2))));
+
+        let mut rom = ROM32KBuiltIn::new();
+        rom.load(longer);
+        rom.load(shorter);
+        assert_eq!((7, 0), (word_to_u16(rom.get(true, address_0)), word_to_u16(rom.get(true, address_2))));
+    }
+
     #[test]
     fn test_computer() {
         assert!(true);
     }
+
+    // what the tests below ask of a computer, so each runs as one case per backend
+    trait Backend {
+        fn load_program(&mut self, instructions: Vec<&str>);
+        fn step(&mut self, reset: Binary, word: u16);
+        fn set_reset_mode(&mut self, reset_mode: ResetMode);
+        fn screen(&self) -> &[Binary];
+        fn get_alu_trace(&self) -> AluTrace;
+        fn get_cpu_trace(&self) -> CpuTrace;
+        fn get_pc(&self) -> u16;
+        fn get_a(&self) -> u16;
+        fn get_d(&self) -> u16;
+        fn get_ram(&self, address: u16) -> u16;
+        fn get_rom(&self, address: u16) -> u16;
+        fn get_last_write(&self) -> Option<(u16, u16)>;
+        fn get_ram_words(&self) -> Vec<u16>;
+        fn set_ram_words(&mut self, words: &[u16]);
+        fn get_rom_words(&self) -> Vec<u16>;
+        fn set_registers(&mut self, pc: u16, a: u16, d: u16);
+    }
+
+    macro_rules! backend {
+        ($computer:ty) => {
+            impl Backend for $computer {
+                fn load_program(&mut self, instructions: Vec<&str>) { <$computer>::load_program(self, instructions) }
+                fn step(&mut self, reset: Binary, word: u16) { <$computer>::step(self, reset, word) }
+                fn set_reset_mode(&mut self, reset_mode: ResetMode) { <$computer>::set_reset_mode(self, reset_mode) }
+                fn screen(&self) -> &[Binary] { <$computer>::screen(self) }
+                fn get_alu_trace(&self) -> AluTrace { <$computer>::get_alu_trace(self) }
+                fn get_cpu_trace(&self) -> CpuTrace { <$computer>::get_cpu_trace(self) }
+                fn get_pc(&self) -> u16 { <$computer>::get_pc(self) }
+                fn get_a(&self) -> u16 { <$computer>::get_a(self) }
+                fn get_d(&self) -> u16 { <$computer>::get_d(self) }
+                fn get_ram(&self, address: u16) -> u16 { <$computer>::get_ram(self, address) }
+                fn get_rom(&self, address: u16) -> u16 { <$computer>::get_rom(self, address) }
+                fn get_last_write(&self) -> Option<(u16, u16)> { <$computer>::get_last_write(self) }
+                fn get_ram_words(&self) -> Vec<u16> { <$computer>::get_ram_words(self) }
+                fn set_ram_words(&mut self, words: &[u16]) { <$computer>::set_ram_words(self, words) }
+                fn get_rom_words(&self) -> Vec<u16> { <$computer>::get_rom_words(self) }
+                fn set_registers(&mut self, pc: u16, a: u16, d: u16) { <$computer>::set_registers(self, pc, a, d) }
+            }
+        };
+    }
+
+    backend!(Computer);
+    backend!(ComputerBuiltIn);
+
+    // @17, D=A, @3, D=D-A
+    const D_MINUS_A: [&str; 5] = [
+        "0000000000010001", "1110110000010000", "0000000000000011", "1110010011010000", "0000000000000000"
+    ];
+
+    fn d_minus_a_trace() -> AluTrace {
+        AluTrace {
+            x: 17, y: 3,
+            zx: false, nx: true, zy: false, ny: false, f: true, no: true,
+            zx_out: 17, nx_out: !17, zy_out: 3, ny_out: 3, f_out: !17 + 3, out: 14,
+            zr: false, ng: false
+        }
+    }
+
+    #[rstest]
+    #[case::gates(Computer::new())]
+    #[case::built_in(ComputerBuiltIn::new())]
+    fn test_computer_alu_trace(#[case] mut computer: impl Backend) {
+        computer.load_program(D_MINUS_A.to_vec());
+        for _ in 0..4 {
+            computer.step(false, 0);
+        }
+        assert_eq!(d_minus_a_trace(), computer.get_alu_trace());
+    }
+
+    fn d_minus_a_cpu_trace() -> CpuTrace {
+        CpuTrace {
+            pc: 3, instruction: 0b1110_0100_1101_0000, reset: false,
+            a: 3, d: 17, in_m: 0,
+            c_instruction: true, y_is_m: false,
+            in_a: 14, write_a: false, write_d: true, write_m: false,
+            out_m: 14, address_m: 3, jump: false, write_pc: false,
+            alu: d_minus_a_trace()
+        }
+    }
+
+    // @6, 0;JMP
+    const JUMP: [&str; 3] = ["0000000000000110", "1110101010000111", "0000000000000000"];
+
+    fn jump_cpu_trace() -> CpuTrace {
+        CpuTrace {
+            pc: 1, instruction: 0b1110_1010_1000_0111, reset: false,
+            a: 6, d: 0, in_m: 0,
+            c_instruction: true, y_is_m: false,
+            in_a: 0, write_a: false, write_d: false, write_m: false,
+            out_m: 0, address_m: 6, jump: true, write_pc: true,
+            alu: AluTrace {
+                x: 0, y: 6,
+                zx: true, nx: false, zy: true, ny: false, f: true, no: false,
+                zx_out: 0, nx_out: 0, zy_out: 0, ny_out: 0, f_out: 0, out: 0,
+                zr: true, ng: false
+            }
+        }
+    }
+
+    #[rstest]
+    #[case::gates(Computer::new())]
+    #[case::built_in(ComputerBuiltIn::new())]
+    fn test_computer_cpu_trace(#[case] mut computer: impl Backend + Clone) {
+        let mut jump = computer.clone();
+        computer.load_program(D_MINUS_A.to_vec());
+        for _ in 0..4 {
+            computer.step(false, 0);
+        }
+        assert_eq!(d_minus_a_cpu_trace(), computer.get_cpu_trace());
+
+        jump.load_program(JUMP.to_vec());
+        for _ in 0..2 {
+            jump.step(false, 0);
+        }
+        assert_eq!(jump_cpu_trace(), jump.get_cpu_trace());
+    }
+
+    // @5, D=A, (LOOP) @2, 0;JMP
+    const SET_D: [&str; 5] = [
+        "0000000000000101", "1110110000010000", "0000000000000010", "1110101010000111", "0000000000000000"
+    ];
+
+    #[rstest]
+    #[case::gates_pc(Computer::new(), ResetMode::Pc, 5)]
+    #[case::gates_power_cycle(Computer::new(), ResetMode::PowerCycle, 0)]
+    #[case::built_in_pc(ComputerBuiltIn::new(), ResetMode::Pc, 5)]
+    #[case::built_in_power_cycle(ComputerBuiltIn::new(), ResetMode::PowerCycle, 0)]
+    fn test_computer_reset_mode(#[case] mut computer: impl Backend, #[case] reset_mode: ResetMode, #[case] d: u16) {
+        computer.load_program(SET_D.to_vec());
+        computer.set_reset_mode(reset_mode);
+        for _ in 0..4 {
+            computer.step(false, 0);
+        }
+        computer.step(true, 0);
+        computer.step(false, 0);
+        assert_eq!((0, d), (computer.get_cpu_trace().pc, computer.get_cpu_trace().d));
+    }
+
+    // @17, D=A, @3, M=D
+    const STORE_17: [&str; 5] = [
+        "0000000000010001", "1110110000010000", "0000000000000011", "1110001100001000", "0000000000000000"
+    ];
+
+    #[rstest]
+    #[case::gates(Computer::new())]
+    #[case::built_in(ComputerBuiltIn::new())]
+    fn test_computer_state_getters(#[case] mut computer: impl Backend) {
+        computer.load_program(STORE_17.to_vec());
+        for _ in 0..3 {
+            computer.step(false, 0);
+        }
+        assert_eq!(None, computer.get_last_write());
+        computer.step(false, 0);
+        assert_eq!(Some((3, 17)), computer.get_last_write());
+        assert_eq!((4, 3, 17), (computer.get_pc(), computer.get_a(), computer.get_d()));
+        assert_eq!((17, 0), (computer.get_ram(3), computer.get_ram(4)));
+        assert_eq!(0b1110_1100_0001_0000, computer.get_rom(1));
+    }
+
+    // @5, D=A, @16385, M=D
+    const SCREEN_5: [&str; 5] = [
+        "0000000000000101", "1110110000010000", "0100000000000001", "1110001100001000", "0000000000000000"
+    ];
+
+    #[rstest]
+    #[case::gates(Computer::new())]
+    #[case::built_in(ComputerBuiltIn::new())]
+    fn test_computer_screen(#[case] mut computer: impl Backend) {
+        computer.load_program(SCREEN_5.to_vec());
+        for _ in 0..4 {
+            computer.step(false, 0);
+        }
+        let screen = computer.screen();
+        assert_eq!(131072, screen.len());
+        assert_eq!((true, false, true), (screen[16], screen[17], screen[18]));
+        assert_eq!(2, screen.iter().filter(|pixel| **pixel).count());
+
+        computer.set_reset_mode(ResetMode::PowerCycle);
+        computer.step(true, 0);
+        assert!(computer.screen().iter().all(|pixel| !pixel));
+    }
+
+    #[rstest]
+    #[case::gates(Computer::new())]
+    #[case::built_in(ComputerBuiltIn::new())]
+    fn test_computer_snapshot_words(#[case] mut computer: impl Backend) {
+        computer.load_program(STORE_17.to_vec());
+        assert_eq!(vec![17, 0b1110_1100_0001_0000, 3, 0b1110_0011_0000_1000, 0], computer.get_rom_words());
+
+        let mut words = vec![0; 24576];
+        words[3] = 5;
+        words[16384] = 1;
+        words[24575] = 0b1000_0000_0000_0000;
+        computer.set_ram_words(&words);
+        assert_eq!(words, computer.get_ram_words());
+        assert_eq!((5, 1), (computer.get_ram(3), computer.get_ram(16384)));
+        assert_eq!((true, false, true), (computer.screen()[0], computer.screen()[1], computer.screen()[131071]));
+
+        // @3, M=D from the registers set
+        computer.set_registers(2, 0, 7);
+        computer.step(false, 0);
+        computer.step(false, 0);
+        assert_eq!((4, 3, 7, 7), (computer.get_pc(), computer.get_a(), computer.get_d(), computer.get_ram(3)));
+    }
 }
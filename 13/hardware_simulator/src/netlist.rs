@@ -0,0 +1,756 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::boolean_arithmetic::{self, add16, inc16};
+use crate::boolean_logic::*;
+use crate::helper::{u16_to_word, u8_to_bits, word_to_u16};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pin {
+    pub name: String,
+    pub width: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection {
+    pub pin: String,
+    pub signal: String,
+    pub width: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    pub id: String,
+    pub chip: String,
+    pub inputs: Vec<Connection>,
+    pub outputs: Vec<Connection>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Netlist {
+    pub name: String,
+    pub inputs: Vec<Pin>,
+    pub outputs: Vec<Connection>,
+    pub wires: Vec<Pin>,
+    pub parts: Vec<Part>,
+}
+
+type Pins = &'static [(&'static str, usize)];
+
+// (chip, inputs, outputs) of every chip implemented in this crate
+const INTERFACES: &[(&str, Pins, Pins)] = &[
+    ("Nand",      &[("a", 1), ("b", 1)],                                   &[("out", 1)]),
+    ("Not",       &[("in", 1)],                                            &[("out", 1)]),
+    ("And",       &[("a", 1), ("b", 1)],                                   &[("out", 1)]),
+    ("Or",        &[("a", 1), ("b", 1)],                                   &[("out", 1)]),
+    ("Xor",       &[("a", 1), ("b", 1)],                                   &[("out", 1)]),
+    ("Mux",       &[("a", 1), ("b", 1), ("sel", 1)],                       &[("out", 1)]),
+    ("DMux",      &[("in", 1), ("sel", 1)],                                &[("a", 1), ("b", 1)]),
+    ("Not16",     &[("in", 16)],                                           &[("out", 16)]),
+    ("And16",     &[("a", 16), ("b", 16)],                                 &[("out", 16)]),
+    ("Or16",      &[("a", 16), ("b", 16)],                                 &[("out", 16)]),
+    ("Mux16",     &[("a", 16), ("b", 16), ("sel", 1)],                     &[("out", 16)]),
+    ("Or8Way",    &[("in", 8)],                                            &[("out", 1)]),
+    ("Mux4Way16", &[("a", 16), ("b", 16), ("c", 16), ("d", 16), ("sel", 2)], &[("out", 16)]),
+    ("Mux8Way16", &[
+        ("a", 16), ("b", 16), ("c", 16), ("d", 16),
+        ("e", 16), ("f", 16), ("g", 16), ("h", 16), ("sel", 3)
+    ],                                                                     &[("out", 16)]),
+    ("DMux4Way",  &[("in", 1), ("sel", 2)],                                &[("a", 1), ("b", 1), ("c", 1), ("d", 1)]),
+    ("DMux8Way",  &[("in", 1), ("sel", 3)],                                &[
+        ("a", 1), ("b", 1), ("c", 1), ("d", 1),
+        ("e", 1), ("f", 1), ("g", 1), ("h", 1)
+    ]),
+    ("HalfAdder", &[("a", 1), ("b", 1)],                                   &[("sum", 1), ("carry", 1)]),
+    ("FullAdder", &[("a", 1), ("b", 1), ("c", 1)],                         &[("sum", 1), ("carry", 1)]),
+    ("Add16",     &[("a", 16), ("b", 16)],                                 &[("out", 16)]),
+    ("Inc16",     &[("in", 16)],                                           &[("out", 16)]),
+    ("ALU",       &[
+        ("x", 16), ("y", 16), ("zx", 1), ("nx", 1),
+        ("zy", 1), ("ny", 1), ("f", 1), ("no", 1)
+    ],                                                                     &[("out", 16), ("zr", 1), ("ng", 1)]),
+    ("DFF",       &[("in", 1)],                                            &[("out", 1)]),
+    ("Bit",       &[("in", 1), ("load", 1)],                               &[("out", 1)]),
+    ("Register",  &[("in", 16), ("load", 1)],                              &[("out", 16)]),
+    ("RAM8",      &[("in", 16), ("load", 1), ("address", 3)],              &[("out", 16)]),
+    ("RAM64",     &[("in", 16), ("load", 1), ("address", 6)],              &[("out", 16)]),
+    ("RAM512",    &[("in", 16), ("load", 1), ("address", 9)],              &[("out", 16)]),
+    ("RAM4K",     &[("in", 16), ("load", 1), ("address", 12)],             &[("out", 16)]),
+    ("RAM16K",    &[("in", 16), ("load", 1), ("address", 14)],             &[("out", 16)]),
+    ("PC",        &[("in", 16), ("load", 1), ("inc", 1), ("reset", 1)],    &[("out", 16)]),
+    ("Screen",    &[("in", 16), ("load", 1), ("address", 13)],             &[("out", 16)]),
+    ("Keyboard",  &[("key_code", 16)],                                     &[("out", 16)]),
+    ("Memory",    &[("in", 16), ("load", 1), ("address", 15), ("key_code", 16)], &[("out", 16)]),
+    ("CPU",       &[("inM", 16), ("instruction", 16), ("reset", 1)],       &[
+        ("outM", 16), ("writeM", 1), ("addressM", 15), ("pc", 16)
+    ]),
+    ("ROM32K",    &[("address", 15)],                                      &[("out", 16)]),
+    ("Computer",  &[("reset", 1), ("key_code", 16)],                       &[]),
+];
+
+pub fn interface(chip: &str) -> Option<(Vec<Pin>, Vec<Pin>)> {
+    let to_pins = |pins: &[(&str, usize)]| -> Vec<Pin> {
+        pins.iter().map(|(name, width)| Pin { name: name.to_string(), width: *width }).collect()
+    };
+    INTERFACES
+        .iter()
+        .find(|(name, _, _)| *name == chip)
+        .map(|(_, inputs, outputs)| (to_pins(inputs), to_pins(outputs)))
+}
+
+pub fn get(chip: &str) -> Option<Netlist> {
+    match chip {
+        "ALU" => Some(alu()),
+        "CPU" => Some(cpu()),
+        "Memory" => Some(memory()),
+        "Computer" => Some(computer()),
+        _ => None,
+    }
+}
+
+pub fn alu() -> Netlist {
+    let mut n = Netlist::new("ALU");
+    n.part("Mux16", &[("a", "x"), ("b", "false"), ("sel", "zx")], &[("out", "x1")]);
+    n.part("Not16", &[("in", "x1")], &[("out", "not_x1")]);
+    n.part("Mux16", &[("a", "x1"), ("b", "not_x1"), ("sel", "nx")], &[("out", "x2")]);
+    n.part("Mux16", &[("a", "y"), ("b", "false"), ("sel", "zy")], &[("out", "y1")]);
+    n.part("Not16", &[("in", "y1")], &[("out", "not_y1")]);
+    n.part("Mux16", &[("a", "y1"), ("b", "not_y1"), ("sel", "ny")], &[("out", "y2")]);
+
+    n.part("And16", &[("a", "x2"), ("b", "y2")], &[("out", "and_xy")]);
+    n.part("Add16", &[("a", "x2"), ("b", "y2")], &[("out", "add_xy")]);
+    n.part("Mux16", &[("a", "and_xy"), ("b", "add_xy"), ("sel", "f")], &[("out", "xy")]);
+    n.part("Not16", &[("in", "xy")], &[("out", "not_xy")]);
+    n.part("Mux16", &[("a", "xy"), ("b", "not_xy"), ("sel", "no")], &[("out", "output")]);
+
+    n.part("Or8Way", &[("in", "output[0..7]")], &[("out", "or_low")]);
+    n.part("Or8Way", &[("in", "output[8..15]")], &[("out", "or_high")]);
+    n.part("Or", &[("a", "or_low"), ("b", "or_high")], &[("out", "or_all")]);
+    n.part("Not", &[("in", "or_all")], &[("out", "zr")]);
+
+    n.output("out", "output");
+    n.output("zr", "zr");
+    n.output("ng", "output[15]");
+    n
+}
+
+pub fn cpu() -> Netlist {
+    let mut n = Netlist::new("CPU");
+    n.part("And", &[("a", "instruction[15]"), ("b", "instruction[3]")], &[("out", "write_m")]);
+
+    n.part("Mux16", &[("a", "out_a"), ("b", "inM"), ("sel", "instruction[12]")], &[("out", "out_a_or_m")]);
+    n.part("ALU", &[
+        ("x", "out_d"),
+        ("y", "out_a_or_m"),
+        ("zx", "instruction[11]"),
+        ("nx", "instruction[10]"),
+        ("zy", "instruction[9]"),
+        ("ny", "instruction[8]"),
+        ("f", "instruction[7]"),
+        ("no", "instruction[6]"),
+    ], &[("out", "out_m"), ("zr", "out_zr"), ("ng", "out_ng")]);
+
+    n.part("Mux16", &[("a", "instruction"), ("b", "out_m"), ("sel", "instruction[15]")], &[("out", "in_a")]);
+    n.part("Not", &[("in", "instruction[15]")], &[("out", "not15")]);
+    n.part("Or", &[("a", "not15"), ("b", "instruction[5]")], &[("out", "write_a")]);
+    n.part("Register", &[("in", "in_a"), ("load", "write_a")], &[("out", "out_a")]);
+
+    n.part("And", &[("a", "instruction[15]"), ("b", "instruction[4]")], &[("out", "write_d")]);
+    n.part("Register", &[("in", "out_m"), ("load", "write_d")], &[("out", "out_d")]);
+
+    n.part("And", &[("a", "instruction[2]"), ("b", "out_ng")], &[("out", "w0")]);
+    n.part("And", &[("a", "instruction[1]"), ("b", "out_zr")], &[("out", "w1")]);
+    n.part("Or", &[("a", "out_zr"), ("b", "out_ng")], &[("out", "out_zr_or_ng")]);
+    n.part("Not", &[("in", "out_zr_or_ng")], &[("out", "out_pg")]);
+    n.part("And", &[("a", "instruction[0]"), ("b", "out_pg")], &[("out", "w2")]);
+    n.part("Or", &[("a", "w0"), ("b", "w1")], &[("out", "w3")]);
+    n.part("Or", &[("a", "w3"), ("b", "w2")], &[("out", "out_jump")]);
+    n.part("And", &[("a", "instruction[15]"), ("b", "out_jump")], &[("out", "write_pc")]);
+    n.part("PC", &[("in", "out_a"), ("load", "write_pc"), ("inc", "true"), ("reset", "reset")], &[("out", "pc_out")]);
+
+    n.output("outM", "out_m");
+    n.output("writeM", "write_m");
+    n.output("addressM", "out_a[0..14]");
+    n.output("pc", "pc_out");
+    n
+}
+
+pub fn memory() -> Netlist {
+    let mut n = Netlist::new("Memory");
+    n.part("DMux", &[("in", "load"), ("sel", "address[14]")], &[("a", "ram_load"), ("b", "screen_load")]);
+    n.part("RAM16K", &[("in", "in"), ("load", "ram_load"), ("address", "address[0..13]")], &[("out", "ram_output")]);
+    n.part("Screen", &[("in", "in"), ("load", "screen_load"), ("address", "address[0..12]")], &[("out", "screen_output")]);
+    n.part("Keyboard", &[("key_code", "key_code")], &[("out", "keyboard_output")]);
+    n.part("Mux4Way16", &[
+        ("a", "ram_output"),
+        ("b", "ram_output"),
+        ("c", "screen_output"),
+        ("d", "keyboard_output"),
+        ("sel", "address[13..14]"),
+    ], &[("out", "output")]);
+
+    n.output("out", "output");
+    n
+}
+
+pub fn computer() -> Netlist {
+    let mut n = Netlist::new("Computer");
+    n.part("ROM32K", &[("address", "pc[0..14]")], &[("out", "instruction")]);
+    n.part("CPU", &[("inM", "in_m"), ("instruction", "instruction"), ("reset", "reset")], &[
+        ("outM", "out_m"),
+        ("writeM", "write_m"),
+        ("addressM", "address_m"),
+        ("pc", "pc"),
+    ]);
+    n.part("Memory", &[
+        ("in", "out_m"),
+        ("load", "write_m"),
+        ("address", "address_m"),
+        ("key_code", "key_code"),
+    ], &[("out", "in_m")]);
+    n
+}
+
+impl Netlist {
+    fn new(chip: &str) -> Self {
+        let (inputs, _) = interface(chip).expect("unknown chip");
+        Netlist {
+            name: chip.to_string(),
+            inputs,
+            outputs: Vec::new(),
+            wires: Vec::new(),
+            parts: Vec::new(),
+        }
+    }
+
+    fn part(&mut self, chip: &str, inputs: &[(&str, &str)], outputs: &[(&str, &str)]) {
+        let (input_pins, output_pins) = interface(chip).expect("unknown chip");
+        let connect = |pins: &[Pin], pin: &str, signal: &str| -> Connection {
+            let width = pins.iter().find(|p| p.name == pin).expect("unknown pin").width;
+            Connection { pin: pin.to_string(), signal: signal.to_string(), width }
+        };
+
+        let inputs: Vec<Connection> = inputs.iter().map(|(pin, signal)| connect(&input_pins, pin, signal)).collect();
+        let outputs: Vec<Connection> = outputs.iter().map(|(pin, signal)| connect(&output_pins, pin, signal)).collect();
+        for output in &outputs {
+            self.wires.push(Pin { name: output.signal.clone(), width: output.width });
+        }
+
+        let count = self.parts.iter().filter(|p| p.chip == chip).count();
+        self.parts.push(Part {
+            id: format!("{}_{}", chip.to_lowercase(), count),
+            chip: chip.to_string(),
+            inputs,
+            outputs,
+        });
+    }
+
+    fn output(&mut self, pin: &str, signal: &str) {
+        let (_, output_pins) = interface(&self.name).expect("unknown chip");
+        let width = output_pins.iter().find(|p| p.name == pin).expect("unknown pin").width;
+        self.outputs.push(Connection { pin: pin.to_string(), signal: signal.to_string(), width });
+    }
+
+    // "name", "name[i]" or "name[i..j]" -> "name"
+    fn signal_name(signal: &str) -> &str {
+        signal.split('[').next().unwrap_or(signal)
+    }
+
+    fn driver(&self, signal: &str) -> Result<String, String> {
+        let name = Self::signal_name(signal);
+        if name == "true" || name == "false" {
+            return Ok(name.to_string());
+        }
+        if self.inputs.iter().any(|p| p.name == name) {
+            return Ok(format!("in_{}", name));
+        }
+        for part in &self.parts {
+            if part.outputs.iter().any(|c| Self::signal_name(&c.signal) == name) {
+                return Ok(part.id.clone());
+            }
+        }
+        Err(format!("undriven signal {}", signal))
+    }
+
+    // the value of every signal, each part computed by this crate's function for its chip,
+    // and the outputs of the chip under their pin names;
+    // the outputs of the parts holding state, like Register and PC, are given with the inputs
+    pub fn evaluate(&self, values: &[(&str, u16)]) -> Result<HashMap<String, u16>, String> {
+        let mut signals: HashMap<String, u16> = values.iter().map(|(name, value)| (name.to_string(), *value)).collect();
+        let mut pending: Vec<&Part> = self.parts
+            .iter()
+            .filter(|part| !part.outputs.iter().all(|c| signals.contains_key(&c.signal)))
+            .collect();
+        while !pending.is_empty() {
+            let ready = pending.iter().position(|part| part.inputs.iter().all(|c| read(&signals, c).is_some()));
+            let part = match ready {
+                Some(i) => pending.remove(i),
+                None => {
+                    let connection = pending
+                        .iter()
+                        .flat_map(|part| &part.inputs)
+                        .find(|c| read(&signals, c).is_none())
+                        .ok_or("no part to evaluate")?;
+                    return Err(format!("undriven signal {}", connection.signal));
+                }
+            };
+            let inputs: HashMap<&str, u16> = part.inputs
+                .iter()
+                .map(|c| (c.pin.as_str(), read(&signals, c).unwrap_or_default()))
+                .collect();
+            let outputs = evaluate_chip(&part.chip, &inputs)?;
+            for connection in &part.outputs {
+                let (_, value) = outputs
+                    .iter()
+                    .find(|(pin, _)| *pin == connection.pin)
+                    .ok_or_else(|| format!("{} has no output {}", part.chip, connection.pin))?;
+                signals.insert(connection.signal.clone(), *value);
+            }
+        }
+        for output in &self.outputs {
+            let value = read(&signals, output).ok_or_else(|| format!("undriven signal {}", output.signal))?;
+            signals.insert(output.pin.clone(), value);
+        }
+        Ok(signals)
+    }
+
+    pub fn to_dot(&self) -> Result<String, String> {
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"{}\" {{", self.name).unwrap();
+        writeln!(dot, "    rankdir=LR;").unwrap();
+        writeln!(dot, "    node [fontname=\"Helvetica\"];").unwrap();
+
+        for input in &self.inputs {
+            writeln!(dot, "    \"in_{}\" [shape=invhouse, label=\"{}[{}]\"];", input.name, input.name, input.width).unwrap();
+        }
+        for output in &self.outputs {
+            writeln!(dot, "    \"out_{}\" [shape=house, label=\"{}[{}]\"];", output.pin, output.pin, output.width).unwrap();
+        }
+        for part in &self.parts {
+            writeln!(dot, "    \"{}\" [shape=box, label=\"{}\"];", part.id, part.chip).unwrap();
+        }
+        let uses_constant = |constant: &str| {
+            self.parts.iter().any(|p| p.inputs.iter().any(|c| c.signal == constant))
+        };
+        for constant in ["true", "false"] {
+            if uses_constant(constant) {
+                writeln!(dot, "    \"{}\" [shape=plaintext];", constant).unwrap();
+            }
+        }
+
+        let edge = |dot: &mut String, from: String, to: &str, connection: &Connection| {
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{} ({})\", penwidth={}];",
+                from, to, connection.signal, connection.width, if connection.width > 1 { 2 } else { 1 }
+            ).unwrap();
+        };
+        for part in &self.parts {
+            for input in &part.inputs {
+                edge(&mut dot, self.driver(&input.signal)?, &part.id, input);
+            }
+        }
+        for output in &self.outputs {
+            edge(&mut dot, self.driver(&output.signal)?, &format!("out_{}", output.pin), output);
+        }
+
+        dot.push_str("}\n");
+        Ok(dot)
+    }
+
+    pub fn to_json(&self) -> String {
+        let pins = |pins: &[Pin]| -> String {
+            pins.iter()
+                .map(|p| format!("{{\"name\":{},\"width\":{}}}", json_string(&p.name), p.width))
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        let connections = |connections: &[Connection]| -> String {
+            connections.iter()
+                .map(|c| format!(
+                    "{{\"pin\":{},\"signal\":{},\"width\":{}}}",
+                    json_string(&c.pin), json_string(&c.signal), c.width
+                ))
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        let parts = self.parts.iter()
+            .map(|p| format!(
+                "{{\"id\":{},\"chip\":{},\"inputs\":[{}],\"outputs\":[{}]}}",
+                json_string(&p.id), json_string(&p.chip), connections(&p.inputs), connections(&p.outputs)
+            ))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        format!(
+            "{{\"name\":{},\"inputs\":[{}],\"outputs\":[{}],\"wires\":[{}],\"parts\":[{}]}}",
+            json_string(&self.name), pins(&self.inputs), connections(&self.outputs), pins(&self.wires), parts
+        )
+    }
+}
+
+// the bits of a connection: "name", "name[i]", "name[i..j]" or a constant; None before it is computed
+fn read(signals: &HashMap<String, u16>, connection: &Connection) -> Option<u16> {
+    let mask = |width: usize| if width >= 16 { 0xffff } else { (1u16 << width) - 1 };
+    match connection.signal.as_str() {
+        "true" => return Some(mask(connection.width)),
+        "false" => return Some(0),
+        _ => {}
+    }
+    let value = *signals.get(Netlist::signal_name(&connection.signal))?;
+    let from = match connection.signal.split_once('[') {
+        Some((_, range)) => range.trim_end_matches(']').split("..").next()?.parse::<usize>().ok()?,
+        None => 0,
+    };
+    Some((value >> from) & mask(connection.width))
+}
+
+// the outputs of a combinational chip, by pin
+fn evaluate_chip(chip: &str, inputs: &HashMap<&str, u16>) -> Result<Vec<(&'static str, u16)>, String> {
+    let value = |pin: &str| inputs.get(pin).copied().unwrap_or_default();
+    let bit = |pin: &str| value(pin) != 0;
+    let word = |pin: &str| u16_to_word(value(pin));
+    let outputs = match chip {
+        "Nand" => vec![("out", nand(bit("a"), bit("b")) as u16)],
+        "Not" => vec![("out", not(bit("in")) as u16)],
+        "And" => vec![("out", and(bit("a"), bit("b")) as u16)],
+        "Or" => vec![("out", or(bit("a"), bit("b")) as u16)],
+        "Xor" => vec![("out", xor(bit("a"), bit("b")) as u16)],
+        "Mux" => vec![("out", mux(bit("a"), bit("b"), bit("sel")) as u16)],
+        "DMux" => {
+            let (a, b) = dmux(bit("in"), bit("sel"));
+            vec![("a", a as u16), ("b", b as u16)]
+        }
+        "Not16" => vec![("out", word_to_u16(not16(word("in"))))],
+        "And16" => vec![("out", word_to_u16(and16(word("a"), word("b"))))],
+        "Or16" => vec![("out", word_to_u16(or16(word("a"), word("b"))))],
+        "Mux16" => vec![("out", word_to_u16(mux16(word("a"), word("b"), bit("sel"))))],
+        "Or8Way" => vec![("out", or8way(u8_to_bits(value("in") as u8)) as u16)],
+        "Mux4Way16" => {
+            let sel = [value("sel") & 1 != 0, value("sel") & 2 != 0];
+            vec![("out", word_to_u16(mux4way16(word("a"), word("b"), word("c"), word("d"), sel)))]
+        }
+        "Add16" => vec![("out", word_to_u16(add16(word("a"), word("b"))))],
+        "Inc16" => vec![("out", word_to_u16(inc16(word("in"))))],
+        "ALU" => {
+            let (out, zr, ng) = boolean_arithmetic::alu(word("x"), word("y"), bit("zx"), bit("nx"), bit("zy"), bit("ny"), bit("f"), bit("no"));
+            vec![("out", word_to_u16(out)), ("zr", zr as u16), ("ng", ng as u16)]
+        }
+        _ => return Err(format!("{} holds state, its outputs are given with the inputs", chip)),
+    };
+    Ok(outputs)
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+    use crate::helper::{bit15_to_bit13, bit15_to_bit14, bit15_to_u16, word_to_bit15};
+
+    // "name[i..j]" -> width j - i + 1, "name[i]" -> 1, "name" -> declared width
+    fn signal_width(netlist: &Netlist, signal: &str) -> usize {
+        if let Some(range) = signal.split('[').nth(1) {
+            let range = range.trim_end_matches(']');
+            return match range.split_once("..") {
+                Some((from, to)) => to.parse::<usize>().unwrap() - from.parse::<usize>().unwrap() + 1,
+                None => 1,
+            };
+        }
+        netlist.inputs.iter().chain(netlist.wires.iter())
+            .find(|p| p.name == signal)
+            .map(|p| p.width)
+            .unwrap_or_else(|| panic!("undeclared signal {}", signal))
+    }
+
+    #[rstest]
+    #[case("ALU")]
+    #[case("CPU")]
+    #[case("Memory")]
+    #[case("Computer")]
+    fn test_netlist_widths(#[case] chip: &str) {
+        let netlist = get(chip).unwrap();
+        for part in &netlist.parts {
+            for connection in part.inputs.iter().chain(part.outputs.iter()) {
+                if connection.signal == "true" || connection.signal == "false" {
+                    continue;
+                }
+                assert_eq!(connection.width, signal_width(&netlist, &connection.signal), "{} {}", part.id, connection.pin);
+            }
+        }
+        for output in &netlist.outputs {
+            assert_eq!(output.width, signal_width(&netlist, &output.signal), "{}", output.pin);
+        }
+    }
+
+    #[rstest]
+    #[case("ALU", 15, 3)]
+    #[case("CPU", 18, 4)]
+    #[case("Memory", 5, 1)]
+    #[case("Computer", 3, 0)]
+    fn test_netlist_parts(#[case] chip: &str, #[case] parts: usize, #[case] outputs: usize) {
+        let netlist = get(chip).unwrap();
+        assert_eq!(parts, netlist.parts.len());
+        assert_eq!(outputs, netlist.outputs.len());
+        for part in &netlist.parts {
+            assert!(interface(&part.chip).is_some());
+        }
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = alu().to_dot().unwrap();
+        assert!(dot.starts_with("digraph \"ALU\" {\n"));
+        assert!(dot.contains("    \"in_x\" [shape=invhouse, label=\"x[16]\"];\n"));
+        assert!(dot.contains("    \"mux16_0\" [shape=box, label=\"Mux16\"];\n"));
+        assert!(dot.contains("    \"in_x\" -> \"mux16_0\" [label=\"x (16)\", penwidth=2];\n"));
+        assert!(dot.contains("    \"false\" -> \"mux16_0\" [label=\"false (16)\", penwidth=2];\n"));
+        assert!(dot.contains("    \"mux16_5\" -> \"out_ng\" [label=\"output[15] (1)\", penwidth=1];\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_undriven_signal() {
+        let mut netlist = Netlist::new("ALU");
+        netlist.part("Not", &[("in", "nowhere")], &[("out", "x1")]);
+        assert_eq!(Err("undriven signal nowhere".to_string()), netlist.to_dot());
+        assert_eq!(Err("undriven signal nowhere".to_string()), netlist.evaluate(&[]));
+    }
+
+    // every ALU function of the Hack instructions, and then some
+    #[test]
+    fn test_evaluate_alu() {
+        let netlist = alu();
+        for (x, y) in [(0, 0), (17, 3), (3, 17), (0xffff, 1), (0x8000, 0x7fff), (12345, 0xabcd)] {
+            for control in 0..64u16 {
+                let bits: Vec<u16> = (0..6).map(|i| (control >> (5 - i)) & 1).collect();
+                let signals = netlist.evaluate(&[
+                    ("x", x), ("y", y), ("zx", bits[0]), ("nx", bits[1]),
+                    ("zy", bits[2]), ("ny", bits[3]), ("f", bits[4]), ("no", bits[5]),
+                ]).unwrap();
+                let (out, zr, ng) = boolean_arithmetic::alu(
+                    u16_to_word(x), u16_to_word(y),
+                    bits[0] != 0, bits[1] != 0, bits[2] != 0, bits[3] != 0, bits[4] != 0, bits[5] != 0
+                );
+                assert_eq!(
+                    (word_to_u16(out), zr as u16, ng as u16),
+                    (signals["out"], signals["zr"], signals["ng"]),
+                    "x={} y={} control={:06b}", x, y, control
+                );
+            }
+        }
+    }
+
+    // the signals of the CPU netlist, given the registers of each cycle, against the CPU's trace
+    #[test]
+    fn test_evaluate_cpu() {
+        let netlist = cpu();
+        let mut cpu = crate::CPU::new();
+        let program: [(u16, u16); 9] = [
+            (0b0000_0000_0001_0001, 0), // @17
+            (0b1110_1100_0001_0000, 0), // D=A
+            (0b0000_0000_0000_0011, 0), // @3
+            (0b1111_0100_1101_0000, 5), // D=D-M
+            (0b1110_0011_0000_1000, 0), // M=D
+            (0b0000_0000_0000_0111, 0), // @7
+            (0b1110_0011_0000_0001, 0), // D;JGT
+            (0b1111_1100_0010_0010, 9), // AM=M;JEQ
+            (0b1110_1010_1000_0111, 0), // 0;JMP
+        ];
+        for (instruction, in_m) in program {
+            cpu.update(true, u16_to_word(in_m), u16_to_word(instruction), false);
+            let trace = cpu.get_trace();
+            let signals = netlist.evaluate(&[
+                ("inM", in_m), ("instruction", instruction), ("reset", 0),
+                ("out_a", trace.a), ("out_d", trace.d), ("pc_out", trace.pc),
+            ]).unwrap();
+            assert_eq!(
+                (
+                    trace.out_m, trace.write_m as u16, trace.address_m,
+                    trace.in_a, trace.write_a as u16, trace.write_d as u16, trace.write_pc as u16,
+                ),
+                (
+                    signals["outM"], signals["writeM"], signals["addressM"],
+                    signals["in_a"], signals["write_a"], signals["write_d"], signals["write_pc"],
+                ),
+                "instruction {:016b}", instruction
+            );
+            cpu.update(false, u16_to_word(in_m), u16_to_word(instruction), false);
+        }
+    }
+
+    // one half of a clock through the parts in their order, each reading its inputs off the signals,
+    // and then the outputs of the chip under their pin names;
+    // stateful gives the outputs of the parts evaluate_chip can't compute
+    fn run_parts(
+        netlist: &Netlist,
+        signals: &mut HashMap<String, u16>,
+        mut stateful: impl FnMut(&str, &HashMap<&str, u16>) -> Vec<(&'static str, u16)>,
+    ) {
+        for part in &netlist.parts {
+            let inputs: HashMap<&str, u16> = part.inputs
+                .iter()
+                .map(|c| (c.pin.as_str(), read(signals, c).unwrap_or_else(|| panic!("undriven signal {}", c.signal))))
+                .collect();
+            let outputs = evaluate_chip(&part.chip, &inputs).unwrap_or_else(|_| stateful(&part.chip, &inputs));
+            for connection in &part.outputs {
+                let (_, value) = outputs.iter().find(|(pin, _)| *pin == connection.pin).unwrap();
+                signals.insert(connection.signal.clone(), *value);
+            }
+        }
+        for output in &netlist.outputs {
+            let value = read(signals, output).unwrap();
+            signals.insert(output.pin.clone(), value);
+        }
+    }
+
+    fn bit15(value: u16) -> [bool; 15] {
+        word_to_bit15(u16_to_word(value))
+    }
+
+    // a Memory whose parts are wired by the netlist, against Memory itself
+    #[test]
+    fn test_run_memory() {
+        let netlist = memory();
+        let mut memory = crate::Memory::new();
+        let mut parts = crate::Memory::new();
+        // (address, in, load, key_code)
+        let writes: [(u16, u16, u16, u16); 10] = [
+            (3, 17, 1, 0),
+            (3, 99, 0, 0),
+            (16383, 0xffff, 1, 0),
+            (16389, 0x00f0, 1, 65),
+            (24576, 5, 1, 65),
+            (3, 0, 0, 0),
+            (16383, 0, 0, 0),
+            (16389, 0, 0, 0),
+            (24576, 0, 0, 75),
+            (20000, 1, 0, 75),
+        ];
+        for (address, input, load, key_code) in writes {
+            for clk in [true, false] {
+                memory.update(clk, u16_to_word(input), load != 0, bit15(address), u16_to_word(key_code));
+                let output = word_to_u16(memory.get(clk, bit15(address)));
+
+                let mut signals: HashMap<String, u16> = [("in", input), ("load", load), ("address", address), ("key_code", key_code)]
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), *value))
+                    .collect();
+                run_parts(&netlist, &mut signals, |chip, inputs| match chip {
+                    "RAM16K" => {
+                        let address = bit15_to_bit14(bit15(inputs["address"]));
+                        parts.ram.update(clk, u16_to_word(inputs["in"]), inputs["load"] != 0, address);
+                        vec![("out", word_to_u16(parts.ram.get(clk, address)))]
+                    }
+                    "Screen" => {
+                        let address = bit15_to_bit13(bit15(inputs["address"]));
+                        parts.screen.update(clk, u16_to_word(inputs["in"]), inputs["load"] != 0, address);
+                        vec![("out", word_to_u16(parts.screen.get(clk, address)))]
+                    }
+                    "Keyboard" => {
+                        parts.keyboard.update(clk, u16_to_word(inputs["key_code"]));
+                        vec![("out", word_to_u16(parts.keyboard.get(clk)))]
+                    }
+                    _ => panic!("{} in Memory", chip),
+                });
+                assert_eq!(output, signals["out"], "address {} clk {}", address, clk);
+            }
+        }
+        assert_eq!(memory.words(), parts.words());
+    }
+
+    // a Computer whose ROM, CPU and memory are wired by the netlist, against Computer itself
+    #[test]
+    fn test_run_computer() {
+        let netlist = computer();
+        let program = vec![
+            "0000000000010001", // @17
+            "1110110000010000", // D=A
+            "0000000000000011", // @3
+            "1110001100001000", // M=D
+            "0000000000000011", // @3
+            "1111110010010000", // D=M-1
+            "0100000000000000", // @16384
+            "1110001100001000", // M=D
+            "0110000000000000", // @24576
+            "1111110000010000", // D=M
+            "0000000000000010", // @2
+            "1110001100000010", // D;JEQ
+            "0000000000001100", // @12
+            "1110101010000111", // 0;JMP
+        ];
+        let mut computer = crate::Computer::new();
+        computer.load_program(program.clone());
+        let mut parts = crate::Computer::new();
+        parts.load_program(program);
+
+        // pc and in_m hold from one half of a clock to the next, as in Computer
+        let mut signals: HashMap<String, u16> = HashMap::from([("pc".to_string(), 0), ("in_m".to_string(), 0)]);
+        let steps = (0..26).map(|i| (i == 0, if i < 12 { 0 } else { 65 }));
+        for (reset, key_code) in steps {
+            computer.step(reset, key_code);
+            for clk in [true, false] {
+                signals.insert("reset".to_string(), reset as u16);
+                signals.insert("key_code".to_string(), key_code);
+                run_parts(&netlist, &mut signals, |chip, inputs| match chip {
+                    "ROM32K" => vec![("out", word_to_u16(parts.rom.get(clk, bit15(inputs["address"]))))],
+                    "CPU" => {
+                        let (in_m, instruction) = (u16_to_word(inputs["inM"]), u16_to_word(inputs["instruction"]));
+                        parts.cpu.update(clk, in_m, instruction, inputs["reset"] != 0);
+                        let (out_m, write_m, pc, address_m) = parts.cpu.get(clk);
+                        vec![
+                            ("outM", word_to_u16(out_m)),
+                            ("writeM", write_m as u16),
+                            ("addressM", bit15_to_u16(address_m)),
+                            ("pc", word_to_u16(pc)),
+                        ]
+                    }
+                    "Memory" => {
+                        let address = bit15(inputs["address"]);
+                        let key_code = u16_to_word(inputs["key_code"]);
+                        parts.memory.update(clk, u16_to_word(inputs["in"]), inputs["load"] != 0, address, key_code);
+                        vec![("out", word_to_u16(parts.memory.get(clk, address)))]
+                    }
+                    _ => panic!("{} in Computer", chip),
+                });
+            }
+            assert_eq!(
+                (computer.get_pc(), computer.get_a(), computer.get_d(), word_to_u16(computer.in_m)),
+                (signals["pc"] & 0x7fff, parts.get_a(), parts.get_d(), signals["in_m"]),
+            );
+        }
+        // the loop stored KBD, 0 then, and 0 - 1 on the screen before the key sent it to @12
+        assert_eq!((0, 0xffff, 12), (computer.get_ram(3), computer.get_ram(16384), computer.get_pc() & !1));
+        assert_eq!((0, 0xffff), (parts.get_ram(3), parts.get_ram(16384)));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json = memory().to_json();
+        assert!(json.starts_with("{\"name\":\"Memory\",\"inputs\":[{\"name\":\"in\",\"width\":16},"));
+        assert!(json.contains("\"outputs\":[{\"pin\":\"out\",\"signal\":\"output\",\"width\":16}]"));
+        assert!(json.contains(
+            "{\"id\":\"dmux_0\",\"chip\":\"DMux\",\"inputs\":[{\"pin\":\"in\",\"signal\":\"load\",\"width\":1},\
+            {\"pin\":\"sel\",\"signal\":\"address[14]\",\"width\":1}],\"outputs\":[{\"pin\":\"a\",\"signal\":\"ram_load\",\"width\":1},\
+            {\"pin\":\"b\",\"signal\":\"screen_load\",\"width\":1}]}"
+        ));
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!("\"a\\\"b\\\\c\\u000a\"", json_string("a\"b\\c\n"));
+    }
+}
@@ -26,6 +26,12 @@ impl DFF {
     pub fn get(self, clk: Binary) -> Binary {
         if clk { self.past_bit } else { self.new_bit }
     }
+
+    // the bit stored without a clock, so both halves of the next one read it
+    pub fn set(&mut self, a: Binary) {
+        self.past_bit = a;
+        self.new_bit = a;
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -45,6 +51,10 @@ impl Bit {
     pub fn get(&self, clk: Binary) -> Binary {
         self.dff.get(clk)
     }
+
+    pub fn set(&mut self, input: Binary) {
+        self.dff.set(input)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -74,6 +84,13 @@ impl Register {
         }
         word
     }
+
+    // the word stored without a clock, as a snapshot is restored or a program loaded
+    pub fn set(&mut self, input: Word) {
+        for (bit, input) in self.bits.iter_mut().zip(input) {
+            bit.set(input);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -115,6 +132,17 @@ impl RAM8 {
             address
         )
     }
+
+    // the words in address order, read without a clock for snapshots
+    fn words(&self) -> Vec<u16> {
+        self.registers.iter().map(|register| word_to_u16(register.get(false))).collect()
+    }
+
+    fn set_words(&mut self, words: &[u16]) {
+        for (register, word) in self.registers.iter_mut().zip(words) {
+            register.set(u16_to_word(*word));
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -160,6 +188,16 @@ impl RAM64 {
             address_high
         )
     }
+
+    fn words(&self) -> Vec<u16> {
+        self.rams.iter().flat_map(RAM8::words).collect()
+    }
+
+    fn set_words(&mut self, words: &[u16]) {
+        for (ram, words) in self.rams.iter_mut().zip(words.chunks(8)) {
+            ram.set_words(words);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -205,6 +243,16 @@ impl RAM512 {
             address_high
         )
     }
+
+    fn words(&self) -> Vec<u16> {
+        self.rams.iter().flat_map(RAM64::words).collect()
+    }
+
+    fn set_words(&mut self, words: &[u16]) {
+        for (ram, words) in self.rams.iter_mut().zip(words.chunks(64)) {
+            ram.set_words(words);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -250,6 +298,16 @@ impl RAM4K {
             address_high
         )
     }
+
+    pub fn words(&self) -> Vec<u16> {
+        self.rams.iter().flat_map(RAM512::words).collect()
+    }
+
+    pub fn set_words(&mut self, words: &[u16]) {
+        for (ram, words) in self.rams.iter_mut().zip(words.chunks(512)) {
+            ram.set_words(words);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -266,13 +324,25 @@ impl RAM4KBuiltIn {
         RAM4KBuiltIn { ram }
     }
 
-    pub fn update(&mut self, _clk: Binary, input: Word, _load: Binary, address: [Binary; 12]) {
-        self.ram[bit12_to_u16(address) as usize] = input;
+    pub fn update(&mut self, clk: Binary, input: Word, load: Binary, address: [Binary; 12]) {
+        if clk && load {
+            self.ram[bit12_to_u16(address) as usize] = input;
+        }
     }
 
     pub fn get(&self, _clk: Binary, address: [Binary; 12]) -> Word {
         self.ram[bit12_to_u16(address) as usize]
     }
+
+    pub fn words(&self) -> Vec<u16> {
+        self.ram.iter().map(|word| word_to_u16(*word)).collect()
+    }
+
+    pub fn set_words(&mut self, words: &[u16]) {
+        for (word, value) in self.ram.iter_mut().zip(words) {
+            *word = u16_to_word(*value);
+        }
+    }
 }
 
 
@@ -319,6 +389,16 @@ impl RAM16K {
             address_high
         )
     }
+
+    pub fn words(&self) -> Vec<u16> {
+        self.rams.iter().flat_map(RAM4K::words).collect()
+    }
+
+    pub fn set_words(&mut self, words: &[u16]) {
+        for (ram, words) in self.rams.iter_mut().zip(words.chunks(4096)) {
+            ram.set_words(words);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -344,6 +424,16 @@ impl RAM16KBuiltIn {
     pub fn get(&self, _clk: Binary, address: [Binary; 14]) -> Word {
         self.ram[bit14_to_u16(address) as usize]
     }
+
+    pub fn words(&self) -> Vec<u16> {
+        self.ram.iter().map(|word| word_to_u16(*word)).collect()
+    }
+
+    pub fn set_words(&mut self, words: &[u16]) {
+        for (word, value) in self.ram.iter_mut().zip(words) {
+            *word = u16_to_word(*value);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -374,6 +464,11 @@ impl PC {
     pub fn get(&self, clk: Binary) -> Word {
         self.counter.get(clk)
     }
+
+    // the counter set between cycles, as a snapshot is restored
+    pub fn set(&mut self, input: Word) {
+        self.counter.set(input);
+    }
 }
 
 #[cfg(test)]
@@ -430,6 +525,21 @@ mod tests {
         assert_eq!(false, bit.get(clk));
     }
 
+    #[test]
+    fn test_register_set() {
+        let mut register = Register::new();
+        let word_5 = u16_to_word(0b0000_0000_0000_0101);
+        let word_7 = u16_to_word(0b0000_0000_0000_0111);
+
+        register.update(true, word_5, true);
+        register.update(false, word_5, true);
+        // without a clock both halves of the next one read the word set
+        register.set(word_7);
+        assert_eq!((word_7, word_7), (register.get(true), register.get(false)));
+        register.update(true, word_5, false);
+        assert_eq!((word_7, word_7), (register.get(true), register.get(false)));
+    }
+
     #[test]
     fn test_register() {
         let mut register = Register::new();
@@ -750,6 +860,21 @@ mod tests {
         assert_eq!(word_i, ram.get(clk, address));
     }
 
+    #[test]
+    fn test_ram4k_built_in() {
+        let word_i = u16_to_word(0b1100_1010_0011_0101);
+        let word_0 = u16_to_word(0b0000_0000_0000_0000);
+        let address = u16_to_12bit(0b0010_0100_1001);
+
+        // only clk with load writes, as in RAM4K
+        for (clk, load) in [(false, false), (false, true), (true, false), (true, true)] {
+            let mut ram = RAM4KBuiltIn::new();
+            ram.update(clk, word_i, load, address);
+            assert_eq!(if clk && load { word_i } else { word_0 }, ram.get(clk, address));
+            assert_eq!(word_0, ram.get(clk, u16_to_12bit(0b0000_0000_0000)));
+        }
+    }
+
     #[test]
     fn test_ram16k() {
         let mut ram = RAM16K::new();
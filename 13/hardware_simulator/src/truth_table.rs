@@ -0,0 +1,534 @@
+use std::fmt;
+use crate::boolean_logic::*;
+use crate::boolean_arithmetic::*;
+use crate::helper::*;
+
+// a column of a .cmp file, printed as |<pad><value><pad>|
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Column {
+    pub name: &'static str,
+    pub width: usize,
+    pub pad: usize,
+}
+
+const fn column(name: &'static str, width: usize, pad: usize) -> Column {
+    Column { name, width, pad }
+}
+
+pub struct Chip {
+    pub name: &'static str,
+    pub inputs: &'static [Column],
+    pub outputs: &'static [Column],
+    eval: fn(&[u16]) -> Vec<u16>,
+}
+
+impl Chip {
+    pub fn eval(&self, inputs: &[u16]) -> Vec<u16> {
+        (self.eval)(inputs)
+    }
+
+    pub fn columns(&self) -> Vec<Column> {
+        self.inputs.iter().chain(self.outputs.iter()).copied().collect()
+    }
+}
+
+fn bit(value: u16) -> Binary {
+    value & 1 == 1
+}
+
+fn sel2(value: u16) -> [Binary; 2] {
+    [bit(value), bit(value >> 1)]
+}
+
+fn sel3(value: u16) -> [Binary; 3] {
+    u8_to_3bit(value as u8)
+}
+
+const A: Column = column("a", 1, 3);
+const B: Column = column("b", 1, 3);
+const C: Column = column("c", 1, 3);
+const IN: Column = column("in", 1, 3);
+const SEL: Column = column("sel", 1, 3);
+const OUT: Column = column("out", 1, 3);
+const A16: Column = column("a", 16, 1);
+const B16: Column = column("b", 16, 1);
+const IN16: Column = column("in", 16, 1);
+const OUT16: Column = column("out", 16, 1);
+const WAY_OUTPUTS: [Column; 8] = [
+    column("a", 1, 2), column("b", 1, 2), column("c", 1, 2), column("d", 1, 2),
+    column("e", 1, 2), column("f", 1, 2), column("g", 1, 2), column("h", 1, 2),
+];
+const WAY16_INPUTS: [Column; 8] = [
+    column("a", 16, 1), column("b", 16, 1), column("c", 16, 1), column("d", 16, 1),
+    column("e", 16, 1), column("f", 16, 1), column("g", 16, 1), column("h", 16, 1),
+];
+
+pub const CHIPS: &[Chip] = &[
+    Chip {
+        name: "Nand",
+        inputs: &[A, B],
+        outputs: &[OUT],
+        eval: |i| vec![nand(bit(i[0]), bit(i[1])) as u16],
+    },
+    Chip {
+        name: "Not",
+        inputs: &[IN],
+        outputs: &[OUT],
+        eval: |i| vec![not(bit(i[0])) as u16],
+    },
+    Chip {
+        name: "And",
+        inputs: &[A, B],
+        outputs: &[OUT],
+        eval: |i| vec![and(bit(i[0]), bit(i[1])) as u16],
+    },
+    Chip {
+        name: "Or",
+        inputs: &[A, B],
+        outputs: &[OUT],
+        eval: |i| vec![or(bit(i[0]), bit(i[1])) as u16],
+    },
+    Chip {
+        name: "Xor",
+        inputs: &[A, B],
+        outputs: &[OUT],
+        eval: |i| vec![xor(bit(i[0]), bit(i[1])) as u16],
+    },
+    Chip {
+        name: "Mux",
+        inputs: &[A, B, SEL],
+        outputs: &[OUT],
+        eval: |i| vec![mux(bit(i[0]), bit(i[1]), bit(i[2])) as u16],
+    },
+    Chip {
+        name: "DMux",
+        inputs: &[IN, SEL],
+        outputs: &[A, B],
+        eval: |i| {
+            let (a, b) = dmux(bit(i[0]), bit(i[1]));
+            vec![a as u16, b as u16]
+        },
+    },
+    Chip {
+        name: "Not16",
+        inputs: &[IN16],
+        outputs: &[OUT16],
+        eval: |i| vec![word_to_u16(not16(u16_to_word(i[0])))],
+    },
+    Chip {
+        name: "And16",
+        inputs: &[A16, B16],
+        outputs: &[OUT16],
+        eval: |i| vec![word_to_u16(and16(u16_to_word(i[0]), u16_to_word(i[1])))],
+    },
+    Chip {
+        name: "Or16",
+        inputs: &[A16, B16],
+        outputs: &[OUT16],
+        eval: |i| vec![word_to_u16(or16(u16_to_word(i[0]), u16_to_word(i[1])))],
+    },
+    Chip {
+        name: "Mux16",
+        inputs: &[A16, B16, column("sel", 1, 2)],
+        outputs: &[OUT16],
+        eval: |i| vec![word_to_u16(mux16(u16_to_word(i[0]), u16_to_word(i[1]), bit(i[2])))],
+    },
+    Chip {
+        name: "Or8Way",
+        inputs: &[column("in", 8, 2)],
+        outputs: &[column("out", 1, 2)],
+        eval: |i| vec![or8way(u8_to_bits(i[0] as u8)) as u16],
+    },
+    Chip {
+        name: "Mux4Way16",
+        inputs: &[
+            WAY16_INPUTS[0], WAY16_INPUTS[1], WAY16_INPUTS[2], WAY16_INPUTS[3],
+            column("sel", 2, 2),
+        ],
+        outputs: &[OUT16],
+        eval: |i| {
+            let w: Vec<Word> = i.iter().map(|v| u16_to_word(*v)).collect();
+            vec![word_to_u16(mux4way16(w[0], w[1], w[2], w[3], sel2(i[4])))]
+        },
+    },
+    Chip {
+        name: "Mux8Way16",
+        inputs: &[
+            WAY16_INPUTS[0], WAY16_INPUTS[1], WAY16_INPUTS[2], WAY16_INPUTS[3],
+            WAY16_INPUTS[4], WAY16_INPUTS[5], WAY16_INPUTS[6], WAY16_INPUTS[7],
+            column("sel", 3, 2),
+        ],
+        outputs: &[OUT16],
+        eval: |i| {
+            let w: Vec<Word> = i.iter().map(|v| u16_to_word(*v)).collect();
+            vec![word_to_u16(mux8way16(w[0], w[1], w[2], w[3], w[4], w[5], w[6], w[7], sel3(i[8])))]
+        },
+    },
+    Chip {
+        name: "DMux4Way",
+        inputs: &[column("in", 1, 2), column("sel", 2, 2)],
+        outputs: &[WAY_OUTPUTS[0], WAY_OUTPUTS[1], WAY_OUTPUTS[2], WAY_OUTPUTS[3]],
+        eval: |i| {
+            let (a, b, c, d) = dmux4way(bit(i[0]), sel2(i[1]));
+            vec![a as u16, b as u16, c as u16, d as u16]
+        },
+    },
+    Chip {
+        name: "DMux8Way",
+        inputs: &[column("in", 1, 2), column("sel", 3, 2)],
+        outputs: &WAY_OUTPUTS,
+        eval: |i| {
+            let (a, b, c, d, e, f, g, h) = dmux8way(bit(i[0]), sel3(i[1]));
+            vec![a as u16, b as u16, c as u16, d as u16, e as u16, f as u16, g as u16, h as u16]
+        },
+    },
+    Chip {
+        name: "HalfAdder",
+        inputs: &[A, B],
+        outputs: &[column("sum", 1, 3), column("carry", 1, 3)],
+        eval: |i| {
+            let (sum, carry) = half_adder(bit(i[0]), bit(i[1]));
+            vec![sum as u16, carry as u16]
+        },
+    },
+    Chip {
+        name: "FullAdder",
+        inputs: &[A, B, C],
+        outputs: &[column("sum", 1, 3), column("carry", 1, 3)],
+        eval: |i| {
+            let (sum, carry) = full_adder(bit(i[0]), bit(i[1]), bit(i[2]));
+            vec![sum as u16, carry as u16]
+        },
+    },
+    Chip {
+        name: "Add16",
+        inputs: &[A16, B16],
+        outputs: &[OUT16],
+        eval: |i| vec![word_to_u16(add16(u16_to_word(i[0]), u16_to_word(i[1])))],
+    },
+    Chip {
+        name: "Inc16",
+        inputs: &[IN16],
+        outputs: &[OUT16],
+        eval: |i| vec![word_to_u16(inc16(u16_to_word(i[0])))],
+    },
+    Chip {
+        name: "ALU",
+        inputs: &[
+            column("x", 16, 1), column("y", 16, 1),
+            column("zx", 1, 1), column("nx", 1, 1), column("zy", 1, 1),
+            column("ny", 1, 1), column("f", 1, 1), column("no", 1, 1),
+        ],
+        outputs: &[OUT16, column("zr", 1, 1), column("ng", 1, 1)],
+        eval: |i| {
+            let (out, zr, ng) = alu(
+                u16_to_word(i[0]), u16_to_word(i[1]),
+                bit(i[2]), bit(i[3]), bit(i[4]), bit(i[5]), bit(i[6]), bit(i[7])
+            );
+            vec![word_to_u16(out), zr as u16, ng as u16]
+        },
+    },
+];
+
+pub fn chip(name: &str) -> Option<&'static Chip> {
+    CHIPS.iter().find(|chip| chip.name == name)
+}
+
+// chips with more input bits than this get sampled 16-bit inputs instead of every combination
+const EXHAUSTIVE_BITS: usize = 16;
+
+// values the official tests use for 16-bit buses
+const SAMPLES: [u16; 8] = [
+    0b0000_0000_0000_0000,
+    0b1111_1111_1111_1111,
+    0b1010_1010_1010_1010,
+    0b0101_0101_0101_0101,
+    0b0011_1100_1100_0011,
+    0b0000_1111_1111_0000,
+    0b0001_0010_0011_0100,
+    0b1001_1000_0111_0110,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TruthTable {
+    pub columns: Vec<Column>,
+    pub rows: Vec<Vec<u16>>,
+}
+
+fn mask(value: u16, width: usize) -> u16 {
+    if width >= 16 { value } else { value & ((1 << width) - 1) }
+}
+
+fn format_header(columns: &[Column]) -> String {
+    let mut line = String::from("|");
+    for column in columns {
+        let total = column.width + column.pad * 2;
+        let left = total.saturating_sub(column.name.len()) / 2;
+        let right = total.saturating_sub(column.name.len() + left);
+        line += &format!("{}{}{}|", " ".repeat(left), column.name, " ".repeat(right));
+    }
+    line
+}
+
+fn format_row(columns: &[Column], values: &[u16]) -> String {
+    let mut line = String::from("|");
+    for (column, value) in columns.iter().zip(values.iter()) {
+        line += &format!(
+            "{}{:0width$b}{}|",
+            " ".repeat(column.pad),
+            mask(*value, column.width),
+            " ".repeat(column.pad),
+            width = column.width
+        );
+    }
+    line
+}
+
+impl fmt::Display for TruthTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", format_header(&self.columns))?;
+        for row in &self.rows {
+            writeln!(f, "{}", format_row(&self.columns, row))?;
+        }
+        Ok(())
+    }
+}
+
+// every combination of the given inputs, first column slowest like the official tables
+fn enumerate(inputs: &[Column]) -> Vec<Vec<u16>> {
+    let mut rows = vec![vec![]];
+    for column in inputs {
+        let mut next = vec![];
+        for row in &rows {
+            for value in 0..(1u32 << column.width) {
+                let mut row: Vec<u16> = row.clone();
+                row.push(value as u16);
+                next.push(row);
+            }
+        }
+        rows = next;
+    }
+    rows
+}
+
+pub fn generate(chip: &Chip) -> TruthTable {
+    let bits: usize = chip.inputs.iter().map(|column| column.width).sum();
+    let inputs = if bits <= EXHAUSTIVE_BITS {
+        enumerate(chip.inputs)
+    } else {
+        // wide inputs take rotated samples, narrow inputs (sel, control bits) are enumerated
+        let narrow: Vec<Column> = chip.inputs.iter().filter(|column| column.width < 16).copied().collect();
+        let mut rows = vec![];
+        for sample in 0..SAMPLES.len() {
+            for narrow_values in enumerate(&narrow) {
+                let mut narrow_values = narrow_values.into_iter();
+                let mut wide = 0;
+                let row = chip.inputs.iter().map(|column| {
+                    if column.width < 16 {
+                        narrow_values.next().unwrap()
+                    } else {
+                        wide += 1;
+                        SAMPLES[(sample + wide - 1) % SAMPLES.len()]
+                    }
+                }).collect();
+                rows.push(row);
+            }
+        }
+        rows
+    };
+    let rows = inputs.into_iter().map(|mut row| {
+        let outputs = chip.eval(&row);
+        row.extend(outputs);
+        row
+    }).collect();
+    TruthTable { columns: chip.columns(), rows }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub line: usize,
+    pub columns: Vec<String>,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "line {}: {} differs", self.line, self.columns.join(", "))?;
+        writeln!(f, "  expected {}", self.expected)?;
+        write!(f, "  actual   {}", self.actual)
+    }
+}
+
+fn parse_cell(cell: &str, width: usize) -> Option<u16> {
+    if cell.len() == width && cell.chars().all(|c| c == '0' || c == '1') {
+        return u16::from_str_radix(cell, 2).ok();
+    }
+    // %D columns, e.g. Mux16's sel
+    cell.parse::<i32>().ok().map(|value| mask(value as u16, width))
+}
+
+fn split_cells(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(|cell| cell.trim()).collect()
+}
+
+// checks every row of a .cmp file against the chip, returning the rows whose outputs differ
+pub fn compare(chip: &Chip, cmp: &str) -> Result<Vec<Mismatch>, String> {
+    let mut lines = cmp.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let header = match lines.next() {
+        Some((_, header)) => split_cells(header),
+        None => return Err("empty compare file".to_string()),
+    };
+
+    let all = chip.columns();
+    let mut columns = vec![];
+    for name in &header {
+        match all.iter().find(|column| column.name == *name) {
+            Some(column) => columns.push(*column),
+            None => return Err(format!("{} has no pin {}", chip.name, name)),
+        }
+    }
+    let input_index: Vec<usize> = chip.inputs.iter().map(|input| {
+        columns.iter().position(|column| column.name == input.name)
+    }).collect::<Option<_>>().ok_or_else(|| format!("compare file lacks an input of {}", chip.name))?;
+
+    let mut mismatches = vec![];
+    for (index, line) in lines {
+        let cells = split_cells(line);
+        if cells.len() != columns.len() {
+            return Err(format!("line {}: expected {} columns, found {}", index + 1, columns.len(), cells.len()));
+        }
+        let mut expected = vec![];
+        for (cell, column) in cells.iter().zip(columns.iter()) {
+            match parse_cell(cell, column.width) {
+                Some(value) => expected.push(value),
+                None => return Err(format!("line {}: cannot read {} in column {}", index + 1, cell, column.name)),
+            }
+        }
+
+        let inputs: Vec<u16> = input_index.iter().map(|i| expected[*i]).collect();
+        let outputs = chip.eval(&inputs);
+        let mut actual = expected.clone();
+        let mut differs = vec![];
+        for (i, column) in columns.iter().enumerate() {
+            if let Some(o) = chip.outputs.iter().position(|output| output.name == column.name) {
+                actual[i] = mask(outputs[o], column.width);
+                if actual[i] != expected[i] {
+                    differs.push(column.name.to_string());
+                }
+            }
+        }
+        if !differs.is_empty() {
+            mismatches.push(Mismatch {
+                line: index + 1,
+                columns: differs,
+                expected: format_row(&columns, &expected),
+                actual: format_row(&columns, &actual),
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+
+    fn normalize(cmp: &str) -> String {
+        cmp.replace("\r\n", "\n")
+    }
+
+    #[rstest]
+    #[case("Not", include_str!("../../../01/Not.cmp"))]
+    #[case("And", include_str!("../../../01/And.cmp"))]
+    #[case("Or", include_str!("../../../01/Or.cmp"))]
+    #[case("Xor", include_str!("../../../01/Xor.cmp"))]
+    #[case("Mux", include_str!("../../../01/Mux.cmp"))]
+    #[case("DMux", include_str!("../../../01/DMux.cmp"))]
+    #[case("DMux4Way", include_str!("../../../01/DMux4Way.cmp"))]
+    #[case("DMux8Way", include_str!("../../../01/DMux8Way.cmp"))]
+    #[case("HalfAdder", include_str!("../../../02/HalfAdder.cmp"))]
+    #[case("FullAdder", include_str!("../../../02/FullAdder.cmp"))]
+    fn test_generate_full_table(#[case] name: &str, #[case] cmp: &str) {
+        assert_eq!(normalize(cmp), generate(chip(name).unwrap()).to_string());
+    }
+
+    #[rstest]
+    #[case("Not16", include_str!("../../../01/Not16.cmp"))]
+    #[case("And16", include_str!("../../../01/And16.cmp"))]
+    #[case("Or16", include_str!("../../../01/Or16.cmp"))]
+    #[case("Mux16", include_str!("../../../01/Mux16.cmp"))]
+    #[case("Or8Way", include_str!("../../../01/Or8Way.cmp"))]
+    #[case("Mux4Way16", include_str!("../../../01/Mux4Way16.cmp"))]
+    #[case("Mux8Way16", include_str!("../../../01/Mux8Way16.cmp"))]
+    #[case("Add16", include_str!("../../../02/Add16.cmp"))]
+    #[case("Inc16", include_str!("../../../02/Inc16.cmp"))]
+    #[case("ALU", include_str!("../../../02/ALU.cmp"))]
+    fn test_generate_header(#[case] name: &str, #[case] cmp: &str) {
+        let table = generate(chip(name).unwrap()).to_string();
+        assert_eq!(normalize(cmp).lines().next(), table.lines().next());
+    }
+
+    #[rstest]
+    #[case("Not", include_str!("../../../01/Not.cmp"))]
+    #[case("And", include_str!("../../../01/And.cmp"))]
+    #[case("Or", include_str!("../../../01/Or.cmp"))]
+    #[case("Xor", include_str!("../../../01/Xor.cmp"))]
+    #[case("Mux", include_str!("../../../01/Mux.cmp"))]
+    #[case("DMux", include_str!("../../../01/DMux.cmp"))]
+    #[case("Not16", include_str!("../../../01/Not16.cmp"))]
+    #[case("And16", include_str!("../../../01/And16.cmp"))]
+    #[case("Or16", include_str!("../../../01/Or16.cmp"))]
+    #[case("Mux16", include_str!("../../../01/Mux16.cmp"))]
+    #[case("Or8Way", include_str!("../../../01/Or8Way.cmp"))]
+    #[case("Mux4Way16", include_str!("../../../01/Mux4Way16.cmp"))]
+    #[case("Mux8Way16", include_str!("../../../01/Mux8Way16.cmp"))]
+    #[case("DMux4Way", include_str!("../../../01/DMux4Way.cmp"))]
+    #[case("DMux8Way", include_str!("../../../01/DMux8Way.cmp"))]
+    #[case("HalfAdder", include_str!("../../../02/HalfAdder.cmp"))]
+    #[case("FullAdder", include_str!("../../../02/FullAdder.cmp"))]
+    #[case("Add16", include_str!("../../../02/Add16.cmp"))]
+    #[case("Inc16", include_str!("../../../02/Inc16.cmp"))]
+    #[case("ALU", include_str!("../../../02/ALU.cmp"))]
+    #[case("ALU", include_str!("../../../02/ALU-nostat.cmp"))]
+    fn test_compare(#[case] name: &str, #[case] cmp: &str) {
+        assert_eq!(Ok(vec![]), compare(chip(name).unwrap(), cmp));
+    }
+
+    #[test]
+    fn test_compare_mismatch() {
+        let cmp = "|   a   |   b   |  out  |\n|   0   |   1   |   1   |\n|   1   |   1   |   1   |\n";
+        let mismatches = compare(chip("Xor").unwrap(), cmp).unwrap();
+        assert_eq!(vec![Mismatch {
+            line: 3,
+            columns: vec!["out".to_string()],
+            expected: "|   1   |   1   |   1   |".to_string(),
+            actual: "|   1   |   1   |   0   |".to_string(),
+        }], mismatches);
+        assert_eq!(
+            "line 3: out differs\n  expected |   1   |   1   |   1   |\n  actual   |   1   |   1   |   0   |",
+            mismatches[0].to_string()
+        );
+    }
+
+    #[rstest]
+    #[case("|   a   |   x   |\n")]
+    #[case("|   a   |  out  |\n")]
+    #[case("|   a   |   b   |  out  |\n|   0   |   2x  |   1   |\n")]
+    fn test_compare_error(#[case] cmp: &str) {
+        assert!(compare(chip("Xor").unwrap(), cmp).is_err());
+    }
+
+    #[test]
+    fn test_generate_sampled() {
+        let table = generate(chip("ALU").unwrap());
+        assert_eq!(SAMPLES.len() * 64, table.rows.len());
+        for row in &table.rows {
+            assert_eq!(chip("ALU").unwrap().eval(&row[..8]), row[8..].to_vec());
+        }
+    }
+}
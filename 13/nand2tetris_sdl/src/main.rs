@@ -0,0 +1,930 @@
+//https://qiita.com/k-yaina60/items/19ee87d1eb740519c11a
+//https://github.com/Rust-SDL2/rust-sdl2
+mod memory_viewer;
+
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::{Rect};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use sdl2::render::{Texture, WindowCanvas};
+use sdl2::video::FullscreenType;
+
+use hack_common::machine::{Backend, Machine, ResetMode};
+use hack_common::keyboard::{self, PressedKeys};
+use hack_common::{disassembler, font};
+use hack_common::program;
+use hack_common::screenshot::{self, Recorder};
+use memory_viewer::MemoryViewer;
+
+const USAGE: &str = "usage: nand2tetris_sdl [program.hack|program.asm] [--watch] [--backend emulator|builtin|gates] [--scale N] [--fullscreen] [--cycles-per-frame N] [--steps N] [--paused] [--hud] [--ram A[..B]]... [--memory] [--record out.gif|frame.png|frame.pbm] [--record-every N]\n       [--headless --cycles N] [--dump-screen out.pbm|out.png] [--dump-ram A[..B]]...";
+
+struct Options {
+    program: Option<String>,
+    // reload the program when the file changes
+    watch: bool,
+    backend: Backend,
+    scale: u32,
+    fullscreen: bool,
+    cycles_per_frame: u32,
+    steps: u32,
+    paused: bool,
+    hud: bool,
+    // RAM words shown in the HUD
+    ram: Option<Vec<u16>>,
+    // open the memory viewer window at start
+    memory: bool,
+    // recording starts with the program when given, or with Ctrl+Shift+G
+    record: Option<String>,
+    record_every: u32,
+    // run --cycles instructions without a window, then dump the screen and RAM
+    headless: bool,
+    cycles: Option<u32>,
+    dump_screen: Option<String>,
+    dump_ram: Vec<u16>,
+}
+
+// 60 frames per second
+const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+// share of a frame that turbo spends running the computer
+const TURBO_BUDGET: Duration = Duration::from_millis(12);
+const MAX_CYCLES_PER_FRAME: u32 = 1 << 20;
+// larger than any display, and the window size can't overflow
+const MAX_SCALE: u32 = 16;
+// the HUD panel is drawn right of the screen
+const HUD_WIDTH: u32 = 160;
+const HUD_MARGIN: usize = 4;
+// R0..R15 unless --ram says otherwise
+const HUD_RAM: std::ops::Range<u16> = 0..16;
+// how often --watch looks at the program file
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+// cycles run headless between drains of the screen writes the emulator keeps for a window
+const HEADLESS_BATCH: u32 = 1 << 16;
+
+struct RunState {
+    paused: bool,
+    turbo: bool,
+    cycles_per_frame: u32,
+    // cycles requested by single-step and step N while paused
+    steps: u32,
+}
+
+impl RunState {
+    fn describe(&self) -> String {
+        if self.paused {
+            "paused".to_string()
+        } else if self.turbo {
+            "turbo".to_string()
+        } else {
+            format!("{} cycles/frame", self.cycles_per_frame)
+        }
+    }
+}
+
+// instructions per second, measured over the last second
+struct Throughput {
+    since: Instant,
+    cycles: u64,
+    per_second: u64,
+}
+
+impl Throughput {
+    fn new() -> Self {
+        Throughput { since: Instant::now(), cycles: 0, per_second: 0 }
+    }
+
+    fn add(&mut self, cycles: u32) {
+        self.cycles += cycles as u64;
+        let elapsed = self.since.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.per_second = (self.cycles as f64 / elapsed.as_secs_f64()) as u64;
+            self.since = Instant::now();
+            self.cycles = 0;
+        }
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Options, String> {
+    let mut options = Options { program: None, watch: false, backend: Backend::default(), scale: 2, fullscreen: false, cycles_per_frame: 1, steps: 100, paused: false, hud: false, ram: None, memory: false, record: None, record_every: 1000, headless: false, cycles: None, dump_screen: None, dump_ram: Vec::new() };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--watch" => options.watch = true,
+            "--backend" => options.backend = args.next().ok_or(format!("{} needs a backend", arg))?.parse()?,
+            "--scale" => options.scale = parse_number(&arg, args.next())?.min(MAX_SCALE),
+            "--cycles-per-frame" => options.cycles_per_frame = parse_number(&arg, args.next())?.min(MAX_CYCLES_PER_FRAME),
+            "--fullscreen" => options.fullscreen = true,
+            "--steps" => options.steps = parse_number(&arg, args.next())?,
+            "--paused" => options.paused = true,
+            "--hud" => options.hud = true,
+            "--memory" => options.memory = true,
+            "--record" => options.record = Some(args.next().ok_or(format!("{} needs a file", arg))?),
+            "--record-every" => options.record_every = parse_number(&arg, args.next())?,
+            "--headless" => options.headless = true,
+            "--cycles" => options.cycles = Some(parse_number(&arg, args.next())?),
+            "--dump-screen" => options.dump_screen = Some(args.next().ok_or(format!("{} needs a file", arg))?),
+            "--dump-ram" => options.dump_ram.extend(parse_addresses(&arg, args.next())?),
+            "--ram" => options.ram.get_or_insert_with(Vec::new).extend(parse_addresses(&arg, args.next())?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}\n{}", arg, USAGE)),
+            _ if options.program.is_none() => options.program = Some(arg),
+            _ => return Err(format!("only one program can be loaded\n{}", USAGE)),
+        }
+    }
+    if options.watch && (options.program.is_none() || options.headless) {
+        return Err(format!("--watch needs a program and a window\n{}", USAGE));
+    }
+    if options.headless && options.cycles.is_none() {
+        return Err(format!("--headless needs --cycles N\n{}", USAGE));
+    }
+    if !options.headless && (options.cycles.is_some() || options.dump_screen.is_some() || !options.dump_ram.is_empty()) {
+        return Err(format!("--cycles, --dump-screen and --dump-ram need --headless\n{}", USAGE));
+    }
+    Ok(options)
+}
+
+fn parse_number(option: &str, value: Option<String>) -> Result<u32, String> {
+    match value.as_deref().map(|value| value.parse::<u32>()) {
+        Some(Ok(number)) if number > 0 => Ok(number),
+        _ => Err(format!("{} needs a positive number", option)),
+    }
+}
+
+// a single address or a range A..B without B
+fn parse_addresses(option: &str, value: Option<String>) -> Result<Vec<u16>, String> {
+    let error = || format!("{} needs an address or a range A..B of addresses up to 24576", option);
+    let value = value.ok_or_else(error)?;
+    let (start, end) = match value.split_once("..") {
+        Some((start, end)) => (start.parse::<u32>(), end.parse::<u32>()),
+        None => (value.parse::<u32>(), value.parse::<u32>().map(|address| address + 1)),
+    };
+    match (start, end) {
+        (Ok(start), Ok(end)) if start < end && end <= 24577 => Ok((start as u16..end as u16).collect()),
+        _ => Err(error()),
+    }
+}
+
+pub fn main() {
+    let options = match parse_args(std::env::args().skip(1).collect()) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let instructions = match &options.program {
+        Some(path) => match program::load(Path::new(path)) {
+            Ok(instructions) => instructions,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => program::FILL.iter().map(|instruction| instruction.to_string()).collect(),
+    };
+    let mut title = match &options.program {
+        Some(path) => window_title(options.backend, Path::new(path)),
+        None => format!("Nand2Tetris ({})", options.backend),
+    };
+    let turbo_batch = turbo_batch(options.backend);
+
+    let mut computer = options.backend.create();
+    computer.load_program(instructions.iter().map(|instruction| instruction.as_str()).collect());
+    let mut recorder = match &options.record {
+        Some(path) => match Recorder::create(Path::new(path), options.record_every as u64) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    if options.headless {
+        if let Err(e) = run_headless(&options, computer.as_mut(), recorder, &mut std::io::stdout()) {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let sdl_context = sdl2::init().unwrap();
+    let video_subsystem = sdl_context.video().unwrap();
+    let window_width = |hud: bool| (if hud { 512 + HUD_WIDTH } else { 512 }) * options.scale;
+    let mut window = video_subsystem.window(&title, window_width(options.hud), 256 * options.scale);
+    window.position_centered().resizable();
+    if options.fullscreen {
+        window.fullscreen_desktop();
+    }
+    let mut canvas = window.build().unwrap().into_canvas().build().unwrap();
+    let texture_creator = canvas.texture_creator();
+    let mut texture = texture_creator.create_texture_streaming(PixelFormatEnum::RGB24, 512, 256).unwrap();
+    let mut pixels = vec![255; 512 * 256 * 3];
+    let mut hud_texture = texture_creator.create_texture_streaming(PixelFormatEnum::RGB24, HUD_WIDTH, 256).unwrap();
+    let mut hud_pixels = vec![0; HUD_WIDTH as usize * 256 * 3];
+    let mut hud = options.hud;
+    let hud_ram: Vec<u16> = options.ram.clone().unwrap_or_else(|| HUD_RAM.collect());
+    let mut throughput = Throughput::new();
+
+    let mut memory_window = video_subsystem.window(&format!("{} - memory", title), memory_viewer::WIDTH, memory_viewer::HEIGHT);
+    memory_window.resizable();
+    if !options.memory {
+        memory_window.hidden();
+    }
+    let mut memory_canvas = memory_window.build().unwrap().into_canvas().build().unwrap();
+    memory_canvas.set_logical_size(memory_viewer::WIDTH, memory_viewer::HEIGHT).unwrap();
+    let memory_texture_creator = memory_canvas.texture_creator();
+    let mut memory_texture = memory_texture_creator
+        .create_texture_streaming(PixelFormatEnum::RGB24, memory_viewer::WIDTH, memory_viewer::HEIGHT)
+        .unwrap();
+    let memory_window_id = memory_canvas.window().id();
+    // only kept while the window is shown
+    let mut viewer = if options.memory { Some(MemoryViewer::new()) } else { None };
+    canvas.window_mut().raise();
+
+    let mut event_pump = sdl_context.event_pump().unwrap();
+    video_subsystem.text_input().start();
+
+    let mut watched = match &options.program {
+        Some(path) if options.watch => Some(Watched::new(PathBuf::from(path))),
+        _ => None,
+    };
+    let mut reset = true;
+    // the mode of Ctrl+R, put back after a loaded program's power cycle
+    let mut reset_mode = ResetMode::default();
+    let mut run = RunState {
+        paused: options.paused,
+        turbo: false,
+        cycles_per_frame: options.cycles_per_frame,
+        steps: 0,
+    };
+    let mut state = String::new();
+    let mut pressed_keys = PressedKeys::new();
+    let mut text_scancode: Option<Scancode> = None;
+
+    'running: loop {
+        let frame_start = Instant::now();
+        for event in event_pump.poll_iter() {
+            if window_id(&event) == Some(memory_window_id) {
+                match event {
+                    Event::Window { win_event: WindowEvent::Close, .. } => {
+                        memory_canvas.window_mut().hide();
+                        viewer = None;
+                    },
+                    _ => {
+                        if let Some(viewer) = &mut viewer {
+                            viewer.handle_event(&event, computer.as_ref());
+                        }
+                    },
+                }
+                continue;
+            }
+            match event {
+                Event::Quit { .. } => break 'running,
+                // a dropped .hack or .asm file replaces the program, and is watched instead with --watch
+                Event::DropFile { filename, .. } => {
+                    let path = PathBuf::from(filename);
+                    if load_dropped(&path, computer.as_mut()) {
+                        reset = true;
+                        title = window_title(options.backend, &path);
+                        let _ = memory_canvas.window_mut().set_title(&format!("{} - memory", title));
+                        state.clear();
+                        if options.watch {
+                            watched = Some(Watched::new(path));
+                        }
+                    }
+                },
+                // with the memory window open, closing the main window does not quit by itself
+                Event::Window { win_event: WindowEvent::Close, .. } => break 'running,
+                Event::KeyDown {
+                    keycode: Some(Keycode::Q),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => break 'running,
+                // Ctrl+R resets the PC, Ctrl+Shift+R power-cycles the computer
+                Event::KeyDown {
+                    keycode: Some(Keycode::R),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    reset_mode = if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                        ResetMode::PowerCycle
+                    } else {
+                        ResetMode::Pc
+                    };
+                    computer.set_reset_mode(reset_mode);
+                    reset = true;
+                },
+                // Ctrl+F switches between window and fullscreen
+                Event::KeyDown {
+                    keycode: Some(Keycode::F),
+                    keymod,
+                    repeat: false,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    let fullscreen = match canvas.window().fullscreen_state() {
+                        FullscreenType::Off => FullscreenType::Desktop,
+                        _ => FullscreenType::Off,
+                    };
+                    let _ = canvas.window_mut().set_fullscreen(fullscreen);
+                },
+                // Ctrl+H shows and hides the HUD
+                Event::KeyDown {
+                    keycode: Some(Keycode::H),
+                    keymod,
+                    repeat: false,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    hud = !hud;
+                    if canvas.window().fullscreen_state() == FullscreenType::Off {
+                        let _ = canvas.window_mut().set_size(window_width(hud), 256 * options.scale);
+                    }
+                },
+                // Ctrl+G saves a screenshot, Ctrl+Shift+G starts and stops recording
+                Event::KeyDown {
+                    keycode: Some(Keycode::G),
+                    keymod,
+                    repeat: false,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    if !keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                        let path = screenshot::unused_path("screenshot", "png");
+                        match screenshot::save(&path, computer.screen()) {
+                            Ok(()) => println!("saved {}", path.display()),
+                            Err(e) => eprintln!("error: {}", e),
+                        }
+                    } else if let Some(recorder) = recorder.take() {
+                        finish_recording(recorder);
+                    } else {
+                        let path = match &options.record {
+                            Some(path) => path.into(),
+                            None => screenshot::unused_path("recording", "gif"),
+                        };
+                        match Recorder::create(&path, options.record_every as u64) {
+                            Ok(new_recorder) => {
+                                println!("recording {}", path.display());
+                                recorder = Some(new_recorder);
+                            },
+                            Err(e) => eprintln!("error: {}", e),
+                        }
+                    }
+                },
+                // Ctrl+M opens and closes the memory window
+                Event::KeyDown {
+                    keycode: Some(Keycode::M),
+                    keymod,
+                    repeat: false,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    if viewer.take().is_some() {
+                        memory_canvas.window_mut().hide();
+                    } else {
+                        viewer = Some(MemoryViewer::new());
+                        memory_canvas.window_mut().show();
+                        canvas.window_mut().raise();
+                    }
+                },
+                // Ctrl+P pauses and resumes
+                Event::KeyDown {
+                    keycode: Some(Keycode::P),
+                    keymod,
+                    repeat: false,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    run.paused = !run.paused;
+                },
+                // Ctrl+S runs one instruction, Ctrl+Shift+S runs --steps instructions, both pause
+                Event::KeyDown {
+                    keycode: Some(Keycode::S),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    run.paused = true;
+                    run.steps += if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) { options.steps } else { 1 };
+                },
+                // Ctrl+T runs as many cycles as a frame allows
+                Event::KeyDown {
+                    keycode: Some(Keycode::T),
+                    keymod,
+                    repeat: false,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    run.turbo = !run.turbo;
+                },
+                // Ctrl+Up and Ctrl+Down double and halve the cycles per frame
+                Event::KeyDown {
+                    keycode: Some(Keycode::Up),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    run.turbo = false;
+                    run.cycles_per_frame = run.cycles_per_frame.saturating_mul(2).min(MAX_CYCLES_PER_FRAME);
+                },
+                Event::KeyDown {
+                    keycode: Some(Keycode::Down),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    run.turbo = false;
+                    run.cycles_per_frame = (run.cycles_per_frame / 2).max(1);
+                },
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    scancode: Some(scancode),
+                    keymod,
+                    ..
+                } if !keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    if let Some(code) = key_code(keycode, keymod) {
+                        pressed_keys.press(scancode.name(), code);
+                    }
+                    text_scancode = Some(scancode);
+                },
+                // the character from the keyboard layout replaces the US layout guess of key_code
+                Event::TextInput { text, .. } => {
+                    let code = text.chars().next().and_then(keyboard::char_code);
+                    if let (Some(code), Some(scancode)) = (code, text_scancode) {
+                        pressed_keys.press(scancode.name(), code);
+                    }
+                },
+                Event::KeyUp {
+                    scancode: Some(scancode),
+                    ..
+                } => {
+                    pressed_keys.release(scancode.name());
+                },
+                Event::Window {
+                    win_event: WindowEvent::FocusLost,
+                    ..
+                } => {
+                    pressed_keys.clear();
+                },
+                _ => {}
+            }
+        }
+
+        if let Some(watched) = &mut watched {
+            if watched.changed() && load_dropped(&watched.path, computer.as_mut()) {
+                reset = true;
+            }
+        }
+
+        let key_code = pressed_keys.code();
+        if reset {
+            computer.step(true, key_code);
+            computer.set_reset_mode(reset_mode);
+            reset = false;
+        }
+        if run.paused {
+            run_cycles(computer.as_mut(), run.steps, key_code, &mut recorder, &mut viewer);
+            throughput.add(run.steps);
+            run.steps = 0;
+        } else if run.turbo {
+            while frame_start.elapsed() < TURBO_BUDGET {
+                run_cycles(computer.as_mut(), turbo_batch, key_code, &mut recorder, &mut viewer);
+                throughput.add(turbo_batch);
+            }
+        } else {
+            run_cycles(computer.as_mut(), run.cycles_per_frame, key_code, &mut recorder, &mut viewer);
+            throughput.add(run.cycles_per_frame);
+        }
+
+        let described = match &recorder {
+            Some(_) => format!("{}, recording", run.describe()),
+            None => run.describe(),
+        };
+        if state != described {
+            state = described;
+            let _ = canvas.window_mut().set_title(&format!("{} [{}]", title, state));
+        }
+
+        if hud {
+            draw_hud(&mut hud_pixels, &hud_lines(computer.as_ref(), &hud_ram, throughput.per_second, &state));
+            display_screen(&mut canvas, &mut texture, &mut pixels, computer.as_mut(), Some((&mut hud_texture, &hud_pixels)));
+        } else {
+            display_screen(&mut canvas, &mut texture, &mut pixels, computer.as_mut(), None);
+        }
+
+        canvas.present();
+
+        if let Some(viewer) = &mut viewer {
+            memory_texture.update(None, viewer.draw(computer.as_ref()), memory_viewer::WIDTH as usize * 3).unwrap();
+            memory_canvas.clear();
+            memory_canvas.copy(&memory_texture, None, None).unwrap();
+            memory_canvas.present();
+        }
+
+        if let Some(rest) = FRAME.checked_sub(frame_start.elapsed()) {
+            std::thread::sleep(rest);
+        }
+    }
+    if let Some(recorder) = recorder {
+        finish_recording(recorder);
+    }
+}
+
+// no SDL is initialized, so no display is needed; the RAM words are written to out one per line
+fn run_headless(options: &Options, computer: &mut dyn Machine, mut recorder: Option<Recorder>, out: &mut dyn Write) -> Result<(), String> {
+    computer.step(true, 0);
+    let mut cycles = options.cycles.unwrap_or(0);
+    while cycles > 0 {
+        let batch = cycles.min(HEADLESS_BATCH);
+        run_cycles(computer, batch, 0, &mut recorder, &mut None);
+        // nothing draws them here, and they would pile up for the whole run
+        computer.get_update_screen_pixels();
+        cycles -= batch;
+    }
+    if let Some(recorder) = recorder {
+        recorder.finish().map_err(|e| e.to_string())?;
+    }
+    if let Some(path) = &options.dump_screen {
+        screenshot::save(Path::new(path), computer.screen()).map_err(|e| format!("{}: {}", path, e))?;
+    }
+    for address in &options.dump_ram {
+        writeln!(out, "RAM[{}] {}", address, computer.get_ram(*address) as i16).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// cycles run between looks at the clock in turbo; the hardware simulators are slow enough to look after each one
+fn turbo_batch(backend: Backend) -> u32 {
+    match backend {
+        Backend::Emulator => 1000,
+        Backend::BuiltIn | Backend::Gates => 1,
+    }
+}
+
+// runs the computer, taking a frame whenever the recorder asks for one and showing writes in the memory viewer
+fn run_cycles(computer: &mut dyn Machine, cycles: u32, key_code: u16, recorder: &mut Option<Recorder>, viewer: &mut Option<MemoryViewer>) {
+    for _ in 0..cycles {
+        computer.step(false, key_code);
+        if let (Some(viewer), Some((address, _))) = (viewer.as_mut(), computer.get_last_write()) {
+            viewer.record_write(address);
+        }
+        if let Some(recorder) = recorder {
+            if recorder.tick() {
+                if let Err(e) = recorder.record(computer.screen()) {
+                    eprintln!("error: {}", e);
+                }
+            }
+        }
+    }
+}
+
+fn window_title(backend: Backend, path: &Path) -> String {
+    format!("Nand2Tetris ({}) - {}", backend, path.display())
+}
+
+// loads a program while running; the computer is power-cycled on the next reset so it starts clean
+fn load_dropped(path: &Path, computer: &mut dyn Machine) -> bool {
+    match program::load(path) {
+        Ok(instructions) => {
+            computer.load_program(instructions.iter().map(|instruction| instruction.as_str()).collect());
+            // for the next reset only
+            computer.set_reset_mode(ResetMode::PowerCycle);
+            println!("loaded {}", path.display());
+            true
+        },
+        Err(e) => {
+            eprintln!("error: {}", e);
+            false
+        },
+    }
+}
+
+// a program file looked at every WATCH_INTERVAL for a new modification time
+struct Watched {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    checked: Instant,
+}
+
+impl Watched {
+    fn new(path: PathBuf) -> Self {
+        let modified = modified(&path);
+        Watched { path, modified, checked: Instant::now() }
+    }
+
+    fn changed(&mut self) -> bool {
+        if self.checked.elapsed() < WATCH_INTERVAL {
+            return false;
+        }
+        self.checked = Instant::now();
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        // a file that is being saved can be gone for a moment
+        modified.is_some()
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// the window an event happened in
+fn window_id(event: &Event) -> Option<u32> {
+    match event {
+        Event::Window { window_id, .. }
+        | Event::KeyDown { window_id, .. }
+        | Event::KeyUp { window_id, .. }
+        | Event::TextEditing { window_id, .. }
+        | Event::TextInput { window_id, .. }
+        | Event::MouseMotion { window_id, .. }
+        | Event::MouseButtonDown { window_id, .. }
+        | Event::MouseButtonUp { window_id, .. }
+        | Event::MouseWheel { window_id, .. } => Some(*window_id),
+        _ => None,
+    }
+}
+
+fn finish_recording(recorder: Recorder) {
+    let frames = recorder.frames();
+    match recorder.finish() {
+        Ok(()) => println!("recorded {} frames", frames),
+        Err(e) => eprintln!("error: {}", e),
+    }
+}
+
+fn display_screen(canvas: &mut WindowCanvas, texture: &mut Texture, pixels: &mut [u8], computer: &mut dyn Machine, hud: Option<(&mut Texture, &[u8])>) {
+    match computer.get_update_screen_pixels() {
+        // only the words written since the last frame have changed
+        Some(updates) => {
+            for (x, y, black) in updates {
+                let i = (y as usize * 512 + x as usize) * 3;
+                let value = if black { 0 } else { 255 };
+                pixels[i..i + 3].copy_from_slice(&[value; 3]);
+            }
+        },
+        None => {
+            let screen = computer.screen();
+            for (pixel, black) in pixels.chunks_mut(3).zip(screen.iter()) {
+                let value = if *black { 0 } else { 255 };
+                pixel.copy_from_slice(&[value; 3]);
+            }
+        },
+    }
+    present_screen(canvas, texture, pixels, hud);
+}
+
+// the screen, and the HUD beside it, are drawn at the largest integer scale that fits the window, centered
+fn present_screen(canvas: &mut WindowCanvas, texture: &mut Texture, pixels: &[u8], hud: Option<(&mut Texture, &[u8])>) {
+    texture.update(None, pixels, 512 * 3).unwrap();
+    let (width, height) = canvas.output_size().unwrap();
+    let content_width = if hud.is_some() { 512 + HUD_WIDTH } else { 512 };
+    let scale = (width / content_width).min(height / 256).max(1);
+    let (w, h) = (content_width * scale, 256 * scale);
+    let x = (width.saturating_sub(w) / 2) as i32;
+    let y = (height.saturating_sub(h) / 2) as i32;
+    canvas.set_draw_color(Color::RGB(0, 0, 0));
+    canvas.clear();
+    canvas.copy(texture, None, Some(Rect::new(x, y, 512 * scale, h))).unwrap();
+    if let Some((hud_texture, hud_pixels)) = hud {
+        hud_texture.update(None, hud_pixels, HUD_WIDTH as usize * 3).unwrap();
+        let hud_x = x + (512 * scale) as i32;
+        canvas.copy(hud_texture, None, Some(Rect::new(hud_x, y, HUD_WIDTH * scale, h))).unwrap();
+    }
+}
+
+// a word as signed decimal and hex
+fn format_word(value: u16) -> String {
+    format!("{:>6} {:04X}", value as i16, value)
+}
+
+// registers, the next instruction, speed and the selected RAM words
+fn hud_lines(computer: &dyn Machine, ram: &[u16], instructions_per_second: u64, state: &str) -> Vec<String> {
+    let pc = computer.get_pc();
+    let instruction = disassembler::disassemble(computer.get_rom(pc)).unwrap_or_else(|| "???".to_string());
+    let mut lines = vec![
+        format!("PC {:>5} {}", pc, instruction),
+        format!("A  {}", format_word(computer.get_a())),
+        format!("D  {}", format_word(computer.get_d())),
+        format!("IPS {}", instructions_per_second),
+        state.to_string(),
+        String::new(),
+    ];
+    let rows = (256 - 2 * HUD_MARGIN) / font::LINE_HEIGHT;
+    for address in ram.iter().take(rows.saturating_sub(lines.len())) {
+        lines.push(format!("{:<10} {}", format!("RAM[{}]", address), format_word(computer.get_ram(*address))));
+    }
+    lines
+}
+
+fn draw_hud(pixels: &mut [u8], lines: &[String]) {
+    pixels.fill(32);
+    let width = HUD_WIDTH as usize;
+    font::draw_text(&lines.join("\n"), |x, y| {
+        let (x, y) = (x + HUD_MARGIN, y + HUD_MARGIN);
+        if x < width && y < 256 {
+            let i = (y * width + x) * 3;
+            pixels[i..i + 3].copy_from_slice(&[224; 3]);
+        }
+    });
+}
+
+// Hack key code of an SDL key; printable keys follow shift and caps lock on a US layout
+fn key_code(keycode: Keycode, keymod: Mod) -> Option<u16> {
+    let function_keys = [
+        Keycode::F1, Keycode::F2, Keycode::F3, Keycode::F4, Keycode::F5, Keycode::F6,
+        Keycode::F7, Keycode::F8, Keycode::F9, Keycode::F10, Keycode::F11, Keycode::F12,
+    ];
+    if let Some(i) = function_keys.iter().position(|key| *key == keycode) {
+        return Some(keyboard::F1 + i as u16);
+    }
+    match keycode {
+        Keycode::Return | Keycode::KpEnter => Some(keyboard::NEWLINE),
+        Keycode::Backspace => Some(keyboard::BACKSPACE),
+        Keycode::Left => Some(keyboard::LEFT_ARROW),
+        Keycode::Up => Some(keyboard::UP_ARROW),
+        Keycode::Right => Some(keyboard::RIGHT_ARROW),
+        Keycode::Down => Some(keyboard::DOWN_ARROW),
+        Keycode::Home => Some(keyboard::HOME),
+        Keycode::End => Some(keyboard::END),
+        Keycode::PageUp => Some(keyboard::PAGE_UP),
+        Keycode::PageDown => Some(keyboard::PAGE_DOWN),
+        Keycode::Insert => Some(keyboard::INSERT),
+        Keycode::Delete => Some(keyboard::DELETE),
+        Keycode::Escape => Some(keyboard::ESC),
+        _ => {
+            let mut c = char::from_u32(keycode as i32 as u32)?;
+            if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                c = keyboard::shifted(c);
+            }
+            if keymod.intersects(Mod::CAPSMOD) && c.is_ascii_alphabetic() {
+                c = if c.is_ascii_lowercase() { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() };
+            }
+            keyboard::char_code(c)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_args_defaults() {
+        let options = parse_args(Vec::new()).unwrap();
+        assert_eq!((None, Backend::Emulator, 2, 1, 100), (options.program, options.backend, options.scale, options.cycles_per_frame, options.steps));
+        assert!(!options.watch && !options.paused && !options.hud && !options.headless);
+        assert_eq!(None, options.ram);
+    }
+
+    #[rstest]
+    #[case("Max.hack --scale 3 --cycles-per-frame 1000 --paused", 3, 1000)]
+    #[case("--scale 1000 --cycles-per-frame 4000000000", MAX_SCALE, MAX_CYCLES_PER_FRAME)]
+    #[case("--cycles-per-frame 1048576 --scale 16", 16, 1048576)]
+    fn test_parse_args_numbers(#[case] line: &str, #[case] scale: u32, #[case] cycles_per_frame: u32) {
+        let options = parse_args(args(line)).unwrap();
+        assert_eq!((scale, cycles_per_frame), (options.scale, options.cycles_per_frame));
+    }
+
+    #[rstest]
+    #[case("--backend emulator", Backend::Emulator)]
+    #[case("--backend builtin", Backend::BuiltIn)]
+    #[case("Max.hack --backend gates --hud", Backend::Gates)]
+    fn test_parse_args_backend(#[case] line: &str, #[case] backend: Backend) {
+        assert_eq!(backend, parse_args(args(line)).unwrap().backend);
+    }
+
+    #[test]
+    fn test_parse_args_ram() {
+        let options = parse_args(args("--ram 16..20 --ram 16384 --headless --cycles 10 --dump-ram 0..2")).unwrap();
+        assert_eq!(Some(vec![16, 17, 18, 19, 16384]), options.ram);
+        assert_eq!(vec![0, 1], options.dump_ram);
+        assert_eq!((true, Some(10)), (options.headless, options.cycles));
+    }
+
+    #[rstest]
+    #[case("--backend nand", "unknown backend nand, use emulator, builtin or gates")]
+    #[case("--backend", "--backend needs a backend")]
+    #[case("--scale 0", "--scale needs a positive number")]
+    #[case("--cycles-per-frame x", "--cycles-per-frame needs a positive number")]
+    #[case("--ram 32..16", "--ram needs an address or a range A..B of addresses up to 24576")]
+    #[case("--bogus", "unknown option --bogus\n")]
+    #[case("-x Max.hack", "unknown option -x\n")]
+    #[case("Max.hack Fill.hack", "only one program can be loaded\n")]
+    #[case("--watch", "--watch needs a program and a window\n")]
+    #[case("Max.hack --watch --headless --cycles 1", "--watch needs a program and a window\n")]
+    #[case("--headless", "--headless needs --cycles N\n")]
+    #[case("--cycles 10", "--cycles, --dump-screen and --dump-ram need --headless\n")]
+    #[case("--dump-ram 0", "--cycles, --dump-screen and --dump-ram need --headless\n")]
+    #[case("--help", "usage: ")]
+    fn test_parse_args_errors(#[case] line: &str, #[case] error: &str) {
+        match parse_args(args(line)) {
+            Ok(_) => panic!("{} parsed", line),
+            Err(err) => assert!(err.starts_with(error), "{}: {}", line, err),
+        }
+    }
+
+    // the RAM and screen dumped after more than one batch of a loop writing the screen
+    #[rstest]
+    #[case(Backend::Emulator, 3 * HEADLESS_BATCH + 5)]
+    #[case(Backend::BuiltIn, 100)]
+    fn test_run_headless(#[case] backend: Backend, #[case] cycles: u32) {
+        let path = temp_dir("headless").join(format!("{}.pbm", backend));
+        let line = format!("--headless --cycles {} --dump-screen {} --dump-ram 3 --dump-ram 16384..16386", cycles, path.display());
+        let options = parse_args(args(&line)).unwrap();
+        let mut computer = backend.create();
+        // @3, M=1, then @16384, M=-1 over and over
+        computer.load_program(vec![
+            "0000000000000011", "1110111111001000", "0100000000000000", "1110111010001000", "0000000000000010", "1110101010000111",
+        ]);
+        let mut out = Vec::new();
+        run_headless(&options, computer.as_mut(), None, &mut out).unwrap();
+
+        assert_eq!("RAM[3] 1\nRAM[16384] -1\nRAM[16385] 0\n", String::from_utf8(out).unwrap());
+        let pbm = std::fs::read(&path).unwrap();
+        assert_eq!(screenshot::pbm(computer.screen()), pbm);
+        assert_eq!((0xff, 0xff, 0), (pbm[11], pbm[12], pbm[13]));
+        // the screen writes were not left to pile up
+        if let Some(pixels) = computer.get_update_screen_pixels() {
+            assert!(pixels.is_empty());
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nand2tetris_sdl_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // a program saved at a time after the last one, as an editor does
+    fn save(path: &Path, text: &str, minutes: u64) {
+        std::fs::write(path, text).unwrap();
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60 * minutes)).unwrap();
+    }
+
+    // what the main loop does with --watch, the interval being over
+    fn reload(watched: &mut Watched, computer: &mut dyn Machine) -> bool {
+        watched.checked -= WATCH_INTERVAL;
+        watched.changed() && load_dropped(&watched.path, computer)
+    }
+
+    #[test]
+    fn test_watched() {
+        let path = temp_dir("watch").join("Watched.hack");
+        save(&path, "0000000000000001\n", 0);
+        let mut computer = Backend::Emulator.create();
+        let mut watched = Watched::new(path.clone());
+        assert!(!reload(&mut watched, computer.as_mut()));
+
+        // once for a new modification time
+        save(&path, "0000000000000010\n", 1);
+        assert!(reload(&mut watched, computer.as_mut()));
+        assert_eq!(2, computer.get_rom(0));
+        assert!(!reload(&mut watched, computer.as_mut()));
+
+        // not before the interval is over
+        save(&path, "0000000000000011\n", 2);
+        assert!(!watched.changed());
+        assert!(reload(&mut watched, computer.as_mut()));
+        assert_eq!(3, computer.get_rom(0));
+
+        // a file gone, as while it is being saved, is not loaded, and is once it is back
+        std::fs::remove_file(&path).unwrap();
+        assert!(!reload(&mut watched, computer.as_mut()));
+        save(&path, "0000000000000100\n", 3);
+        assert!(reload(&mut watched, computer.as_mut()));
+        assert!(!reload(&mut watched, computer.as_mut()));
+        assert_eq!(4, computer.get_rom(0));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[rstest]
+    #[case(Some("7"), Ok(7))]
+    #[case(Some("4294967295"), Ok(u32::MAX))]
+    #[case(Some("0"), Err("--steps needs a positive number".to_string()))]
+    #[case(Some("-1"), Err("--steps needs a positive number".to_string()))]
+    #[case(Some("4294967296"), Err("--steps needs a positive number".to_string()))]
+    #[case(None, Err("--steps needs a positive number".to_string()))]
+    fn test_parse_number(#[case] value: Option<&str>, #[case] output: Result<u32, String>) {
+        assert_eq!(output, parse_number("--steps", value.map(String::from)));
+    }
+
+    #[rstest]
+    #[case(Some("5"), Ok(vec![5]))]
+    #[case(Some("16..19"), Ok(vec![16, 17, 18]))]
+    #[case(Some("24576"), Ok(vec![24576]))]
+    #[case(Some("24575..24577"), Ok(vec![24575, 24576]))]
+    #[case(Some("5..5"), Err(()))]
+    #[case(Some("24577"), Err(()))]
+    #[case(Some("0..24578"), Err(()))]
+    #[case(Some("16.."), Err(()))]
+    #[case(Some("x"), Err(()))]
+    #[case(None, Err(()))]
+    fn test_parse_addresses(#[case] value: Option<&str>, #[case] output: Result<Vec<u16>, ()>) {
+        let error = "--ram needs an address or a range A..B of addresses up to 24576".to_string();
+        assert_eq!(output.map_err(|_| error), parse_addresses("--ram", value.map(String::from)));
+    }
+}
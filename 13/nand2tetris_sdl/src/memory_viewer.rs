@@ -0,0 +1,208 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseWheelDirection;
+
+use hack_common::disassembler;
+use hack_common::font;
+use hack_common::machine::Machine;
+
+pub const WIDTH: u32 = 576;
+pub const HEIGHT: u32 = 400;
+const MARGIN: usize = 4;
+// rows of the RAM and ROM panes under their title
+const ROWS: usize = (HEIGHT as usize - 2 * MARGIN) / font::LINE_HEIGHT - 1;
+const RAM_X: usize = MARGIN;
+const ROM_X: usize = 150;
+const HEAT_X: usize = 312;
+const HEAT_Y: usize = MARGIN + font::LINE_HEIGHT;
+const RAM_WORDS: u16 = 24577;
+
+const BACKGROUND: [u8; 3] = [32, 32, 32];
+const TEXT: [u8; 3] = [224, 224, 224];
+const DIM: [u8; 3] = [128, 128, 128];
+const WRITTEN: [u8; 3] = [192, 144, 0];
+const CURRENT: [u8; 3] = [0, 72, 160];
+const HEAT: [u8; 3] = [255, 96, 0];
+
+// what a highlight loses per frame; a written word fades out in about a second
+const RAM_FADE: u8 = 4;
+const SCREEN_FADE: u8 = 2;
+const SCREEN_WRITE_HEAT: u8 = 48;
+
+// RAM in hex and decimal with the words written lately highlighted,
+// the ROM around the PC, and a heat map of the screen words written lately
+pub struct MemoryViewer {
+    pixels: Vec<u8>,
+    ram_heat: Vec<u8>,
+    screen_heat: Vec<u8>,
+    ram_top: u16,
+    // None follows the PC
+    rom_top: Option<u16>,
+    mouse_x: i32,
+}
+
+impl MemoryViewer {
+    pub fn new() -> Self {
+        MemoryViewer {
+            pixels: vec![0; WIDTH as usize * HEIGHT as usize * 3],
+            ram_heat: vec![0; RAM_WORDS as usize],
+            screen_heat: vec![0; 8192],
+            ram_top: 0,
+            rom_top: None,
+            mouse_x: 0,
+        }
+    }
+
+    pub fn record_write(&mut self, address: u16) {
+        if let Some(heat) = self.ram_heat.get_mut(address as usize) {
+            *heat = 255;
+        }
+        if (16384 /* SCREEN */..24576 /* KBD */).contains(&address) {
+            let heat = &mut self.screen_heat[address as usize - 16384];
+            *heat = heat.saturating_add(SCREEN_WRITE_HEAT);
+        }
+    }
+
+    // the window has a logical size of WIDTH x HEIGHT, so mouse positions are in pixels of the view
+    pub fn handle_event(&mut self, event: &Event, machine: &dyn Machine) {
+        match event {
+            Event::MouseMotion { x, .. } => self.mouse_x = *x,
+            Event::MouseWheel { y, direction, .. } => {
+                let y = if *direction == MouseWheelDirection::Flipped { -*y } else { *y };
+                if (self.mouse_x as usize) < ROM_X {
+                    self.scroll_ram(-3 * y);
+                } else {
+                    let top = self.rom_top.unwrap_or_else(|| self.pc_top(machine));
+                    self.rom_top = Some((top as i32 - 3 * y).clamp(0, 32768 - ROWS as i32) as u16);
+                }
+            },
+            Event::KeyDown { keycode: Some(keycode), .. } => match keycode {
+                Keycode::Up => self.scroll_ram(-1),
+                Keycode::Down => self.scroll_ram(1),
+                Keycode::PageUp => self.scroll_ram(-(ROWS as i32)),
+                Keycode::PageDown => self.scroll_ram(ROWS as i32),
+                Keycode::Num0 => self.ram_top = 0,
+                // the stack from its top down, Jack OS heap, screen
+                Keycode::S => self.show_ram(machine.get_ram(0 /* SP */).saturating_sub(ROWS as u16 / 2)),
+                Keycode::H => self.show_ram(2048),
+                Keycode::C => self.show_ram(16384),
+                Keycode::P => self.rom_top = None,
+                _ => {},
+            },
+            _ => {},
+        }
+    }
+
+    fn scroll_ram(&mut self, rows: i32) {
+        self.show_ram((self.ram_top as i32 + rows).clamp(0, u16::MAX as i32) as u16);
+    }
+
+    fn show_ram(&mut self, address: u16) {
+        self.ram_top = address.min(RAM_WORDS - ROWS as u16);
+    }
+
+    // the PC a quarter down the pane
+    fn pc_top(&self, machine: &dyn Machine) -> u16 {
+        machine.get_pc().saturating_sub(ROWS as u16 / 4).min(32768 - ROWS as u16)
+    }
+
+    pub fn draw(&mut self, machine: &dyn Machine) -> &[u8] {
+        for pixel in self.pixels.chunks_mut(3) {
+            pixel.copy_from_slice(&BACKGROUND);
+        }
+        self.text(RAM_X, MARGIN, "RAM   hex    dec", DIM);
+        self.text(ROM_X, MARGIN, "ROM", DIM);
+        self.text(HEAT_X, MARGIN, "screen writes", DIM);
+
+        for row in 0..ROWS {
+            let address = self.ram_top + row as u16;
+            let y = MARGIN + (row + 1) * font::LINE_HEIGHT;
+            let heat = self.ram_heat[address as usize];
+            if heat > 0 {
+                self.fill(RAM_X - 2, y - 1, ROM_X - RAM_X - 4, font::LINE_HEIGHT, blend(BACKGROUND, WRITTEN, heat));
+            }
+            let value = machine.get_ram(address);
+            self.text(RAM_X, y, &format!("{:5} {:04X} {:>6}", address, value, value as i16), TEXT);
+        }
+
+        let pc = machine.get_pc();
+        let rom_top = self.rom_top.unwrap_or_else(|| self.pc_top(machine));
+        for row in 0..ROWS {
+            let address = rom_top + row as u16;
+            let y = MARGIN + (row + 1) * font::LINE_HEIGHT;
+            if address == pc {
+                self.fill(ROM_X - 2, y - 1, HEAT_X - ROM_X - 8, font::LINE_HEIGHT, CURRENT);
+            }
+            let instruction = disassembler::disassemble(machine.get_rom(address)).unwrap_or_else(|| "???".to_string());
+            let marker = if address == pc { '>' } else { ' ' };
+            self.text(ROM_X, y, &format!("{}{:5} {}", marker, address, instruction), TEXT);
+        }
+
+        // a word is 16 pixels wide and 1 high, drawn 8 wide and 1/2 high
+        self.fill(HEAT_X - 1, HEAT_Y - 1, 258, 130, DIM);
+        self.fill(HEAT_X, HEAT_Y, 256, 128, [0, 0, 0]);
+        for (word, heat) in self.screen_heat.iter().enumerate() {
+            if *heat > 0 {
+                let x = HEAT_X + word % 32 * 8;
+                let y = HEAT_Y + word / 32 / 2;
+                for dx in 0..8 {
+                    let i = (y * WIDTH as usize + x + dx) * 3;
+                    let color = blend([0, 0, 0], HEAT, *heat);
+                    for (channel, value) in self.pixels[i..i + 3].iter_mut().zip(color) {
+                        *channel = (*channel).max(value);
+                    }
+                }
+            }
+        }
+
+        let mut y = HEAT_Y + 128 + font::LINE_HEIGHT;
+        for (i, name) in ["SP", "LCL", "ARG", "THIS", "THAT"].iter().enumerate() {
+            self.text(HEAT_X, y, &format!("{:<5}{:5}", name, machine.get_ram(i as u16)), TEXT);
+            y += font::LINE_HEIGHT;
+        }
+        y += font::LINE_HEIGHT;
+        let help = [
+            "wheel, Up/Down, PgUp/PgDn: scroll",
+            "0: RAM[0]  s: stack  h: heap",
+            "c: screen  p: ROM follows the PC",
+        ];
+        for line in help {
+            self.text(HEAT_X, y, line, DIM);
+            y += font::LINE_HEIGHT;
+        }
+
+        for heat in self.ram_heat.iter_mut() {
+            *heat = heat.saturating_sub(RAM_FADE);
+        }
+        for heat in self.screen_heat.iter_mut() {
+            *heat = heat.saturating_sub(SCREEN_FADE);
+        }
+        &self.pixels
+    }
+
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 3]) {
+        for row in y..(y + height).min(HEIGHT as usize) {
+            for column in x..(x + width).min(WIDTH as usize) {
+                let i = (row * WIDTH as usize + column) * 3;
+                self.pixels[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    fn text(&mut self, x: usize, y: usize, text: &str, color: [u8; 3]) {
+        let pixels = &mut self.pixels;
+        font::draw_text(text, |dx, dy| {
+            let (x, y) = (x + dx, y + dy);
+            if x < WIDTH as usize && y < HEIGHT as usize {
+                let i = (y * WIDTH as usize + x) * 3;
+                pixels[i..i + 3].copy_from_slice(&color);
+            }
+        });
+    }
+}
+
+// from a to b by amount / 255
+fn blend(a: [u8; 3], b: [u8; 3], amount: u8) -> [u8; 3] {
+    let mix = |a: u8, b: u8| ((a as u32 * (255 - amount as u32) + b as u32 * amount as u32) / 255) as u8;
+    [mix(a[0], b[0]), mix(a[1], b[1]), mix(a[2], b[2])]
+}
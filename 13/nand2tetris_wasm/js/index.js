@@ -0,0 +1,126 @@
+import("../pkg/index.js")
+  .then((nand2tetris) => {
+    const canvas = document.getElementById("canvas");
+    const pause = document.getElementById("pause");
+    const registers = document.getElementById("registers");
+    const parameters = new URLSearchParams(location.search);
+
+    // the backend is chosen when the page loads
+    const backend = document.getElementById("backend");
+    backend.value = parameters.get("backend") || "emulator";
+    backend.addEventListener("change", () => {
+      parameters.set("backend", backend.value);
+      location.search = parameters.toString();
+    });
+
+    const showRegisters = () => {
+      registers.textContent = nand2tetris.isPaused()
+        ? `PC ${nand2tetris.pc()} A ${nand2tetris.a()} D ${nand2tetris.d()}`
+        : "";
+    };
+
+    document.getElementById("program").addEventListener("change", async (event) => {
+      const file = event.target.files[0];
+      if (file) {
+        try {
+          nand2tetris.loadProgramBytes(new Uint8Array(await file.arrayBuffer()), file.name);
+        } catch (error) {
+          alert(error);
+        }
+      }
+      showRegisters();
+      canvas.focus();
+    });
+    // a link with the program in its fragment, loaded when opened, or when pasted into this page's address
+    document.getElementById("link").addEventListener("click", async () => {
+      try {
+        const link = nand2tetris.programLink();
+        try {
+          await navigator.clipboard.writeText(link);
+        } catch (error) {
+          prompt("Link to the program", link);
+        }
+      } catch (error) {
+        alert(error);
+      }
+      canvas.focus();
+    });
+    window.addEventListener("hashchange", () => {
+      try {
+        nand2tetris.loadProgramLink(location.hash);
+      } catch (error) {
+        alert(error);
+      }
+      showRegisters();
+    });
+    pause.addEventListener("click", () => {
+      if (nand2tetris.isPaused()) {
+        nand2tetris.resume();
+      } else {
+        nand2tetris.pause();
+      }
+      pause.textContent = nand2tetris.isPaused() ? "Resume" : "Pause";
+      showRegisters();
+      canvas.focus();
+    });
+    document.getElementById("speed").addEventListener("change", (event) => {
+      if (event.target.value === "max") {
+        nand2tetris.setTimeBudget(8);
+      } else {
+        nand2tetris.setSpeed(Number(event.target.value));
+      }
+      canvas.focus();
+    });
+    const ips = document.getElementById("ips");
+    setInterval(() => {
+      try {
+        ips.textContent = `${Math.round(nand2tetris.instructionsPerSecond()).toLocaleString()} instructions/s`;
+        // a breakpoint or the debugger's Run button may have paused or resumed it
+        pause.textContent = nand2tetris.isPaused() ? "Resume" : "Pause";
+      } catch (error) {
+        // not started yet
+      }
+    }, 1000);
+    const slot = document.getElementById("slot");
+    document.getElementById("save").addEventListener("click", () => {
+      try {
+        nand2tetris.saveSnapshot(slot.value);
+      } catch (error) {
+        alert(error);
+      }
+      canvas.focus();
+    });
+    document.getElementById("load").addEventListener("click", () => {
+      try {
+        if (!nand2tetris.loadSnapshot(slot.value)) {
+          alert(`Nothing saved in ${slot.options[slot.selectedIndex].text}`);
+        }
+      } catch (error) {
+        alert(error);
+      }
+      showRegisters();
+      canvas.focus();
+    });
+    // the text is typed into the keyboard a key at a time
+    const typeText = (text) => {
+      try {
+        nand2tetris.typeText(text);
+      } catch (error) {
+        alert(error);
+      }
+      canvas.focus();
+    };
+    document.getElementById("type").addEventListener("click", () => {
+      typeText(document.getElementById("text").value);
+    });
+    canvas.addEventListener("paste", (event) => {
+      event.preventDefault();
+      typeText(event.clipboardData.getData("text"));
+    });
+    document.getElementById("step").addEventListener("click", () => {
+      nand2tetris.step(1);
+      showRegisters();
+      canvas.focus();
+    });
+  })
+  .catch(console.error);
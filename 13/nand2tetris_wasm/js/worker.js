@@ -0,0 +1,4 @@
+// the computer of a page opened with ?worker
+import("../pkg/index.js")
+  .then((nand2tetris) => nand2tetris.runWorker())
+  .catch(console.error);
@@ -0,0 +1,216 @@
+use hack_common::link;
+use hack_common::machine::ResetMode;
+use hack_common::program;
+use hack_common::snapshot::Snapshot;
+use wasm_bindgen::prelude::*;
+
+use crate::browser;
+use crate::engine::{self, Running};
+use crate::storage;
+use crate::worker::{self, Command, Status};
+
+// the functions a web page calls to control the computer, e.g. to run a program from a file input;
+// with a worker they send it commands and answer from what it told last
+
+fn with_running<T>(f: impl FnOnce(&mut Running) -> T) -> Result<T, JsValue> {
+    engine::with_running(f).map_err(|err| JsValue::from_str(&err.to_string()))
+}
+
+fn control(command: Command, f: impl FnOnce(&mut Running)) -> Result<(), JsValue> {
+    if worker::is_host() {
+        worker::send(command).map_err(|err| JsValue::from_str(&err.to_string()))
+    } else {
+        with_running(f)
+    }
+}
+
+fn query<T>(status: impl FnOnce(Status) -> T, f: impl FnOnce(&mut Running) -> T) -> Result<T, JsValue> {
+    if worker::is_host() {
+        worker::status_of_worker()
+            .map(status)
+            .map_err(|err| JsValue::from_str(&err.to_string()))
+    } else {
+        with_running(f)
+    }
+}
+
+// a .hack or .asm program; without a file name the text tells which it is
+#[wasm_bindgen(js_name = loadProgram)]
+pub fn load_program(text: &str, name: Option<String>) -> Result<(), JsValue> {
+    let instructions = match name {
+        Some(name) => program::parse(&name, text),
+        None => program::parse_text(text),
+    }
+    .map_err(|err| JsValue::from_str(&err))?;
+    control(Command::Load(instructions.clone()), |running| running.load_program(&instructions))
+}
+
+// the contents of a file as a Uint8Array, e.g. from File.arrayBuffer()
+#[wasm_bindgen(js_name = loadProgramBytes)]
+pub fn load_program_bytes(bytes: &[u8], name: Option<String>) -> Result<(), JsValue> {
+    let text = std::str::from_utf8(bytes).map_err(|err| JsValue::from_str(&format!("not a text file: {}", err)))?;
+    load_program(text, name)
+}
+
+// the program of a #program=... fragment, as programLink makes; false when the fragment has none
+#[wasm_bindgen(js_name = loadProgramLink)]
+pub fn load_program_link(fragment: &str) -> Result<bool, JsValue> {
+    match link::from_fragment(fragment) {
+        Some(text) => {
+            load_program(&text.map_err(|err| JsValue::from_str(&err))?, None)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// the page address with the program in the ROM in its fragment, as .hack
+#[wasm_bindgen(js_name = programLink)]
+pub fn program_link() -> Result<String, JsValue> {
+    let text = if worker::is_host() {
+        worker::program_of_worker().map_err(|err| JsValue::from_str(&err.to_string()))?
+    } else {
+        with_running(Running::program_text)?
+    };
+    if text.is_empty() {
+        return Err(JsValue::from_str("no program loaded"));
+    }
+    let address = browser::page_address().map_err(|err| JsValue::from_str(&err.to_string()))?;
+    Ok(format!("{}#{}", address, link::to_fragment(&text)))
+}
+
+#[wasm_bindgen]
+pub fn reset() -> Result<(), JsValue> {
+    control(Command::Reset, |running| running.reset(ResetMode::Pc))
+}
+
+#[wasm_bindgen(js_name = powerCycle)]
+pub fn power_cycle() -> Result<(), JsValue> {
+    control(Command::PowerCycle, |running| running.reset(ResetMode::PowerCycle))
+}
+
+#[wasm_bindgen]
+pub fn pause() -> Result<(), JsValue> {
+    control(Command::Pause, Running::pause)
+}
+
+#[wasm_bindgen]
+pub fn resume() -> Result<(), JsValue> {
+    control(Command::Resume, Running::resume)
+}
+
+#[wasm_bindgen(js_name = isPaused)]
+pub fn is_paused() -> Result<bool, JsValue> {
+    query(|status| status.paused, |running| running.is_paused())
+}
+
+// runs whether paused or not
+#[wasm_bindgen]
+pub fn step(cycles: u32) -> Result<(), JsValue> {
+    control(Command::Step(cycles), |running| running.step(cycles))
+}
+
+// the cycles run every frame, at least 1, instead of a time budget
+#[wasm_bindgen(js_name = setSpeed)]
+pub fn set_speed(cycles_per_frame: u32) -> Result<(), JsValue> {
+    control(Command::SetSpeed(cycles_per_frame), |running| running.set_speed(cycles_per_frame))
+}
+
+#[wasm_bindgen]
+pub fn speed() -> Result<u32, JsValue> {
+    query(|status| status.speed, |running| running.speed())
+}
+
+// as many cycles as fit in the milliseconds every frame, or cycles_per_frame again for 0
+#[wasm_bindgen(js_name = setTimeBudget)]
+pub fn set_time_budget(milliseconds: f64) -> Result<(), JsValue> {
+    control(Command::SetTimeBudget(milliseconds), |running| running.set_time_budget(milliseconds))
+}
+
+// 0 while running a number of cycles every frame
+#[wasm_bindgen(js_name = timeBudget)]
+pub fn time_budget() -> Result<f64, JsValue> {
+    query(|status| status.time_budget, |running| running.time_budget())
+}
+
+#[wasm_bindgen(js_name = instructionsPerSecond)]
+pub fn instructions_per_second() -> Result<f64, JsValue> {
+    query(|status| status.instructions_per_second, |running| running.instructions_per_second())
+}
+
+#[wasm_bindgen]
+pub fn pc() -> Result<u16, JsValue> {
+    query(|status| status.pc, |running| running.machine().get_pc())
+}
+
+#[wasm_bindgen]
+pub fn a() -> Result<u16, JsValue> {
+    query(|status| status.a, |running| running.machine().get_a())
+}
+
+#[wasm_bindgen]
+pub fn d() -> Result<u16, JsValue> {
+    query(|status| status.d, |running| running.machine().get_d())
+}
+
+// a RAM or screen word as if the program had written it; KBD is left to the keyboard
+#[wasm_bindgen(js_name = setRam)]
+pub fn set_ram(address: u16, value: u16) -> Result<(), JsValue> {
+    control(Command::SetRam(address, value), |running| running.machine().set_ram(address, value))
+}
+
+// the computer pauses when the PC gets to a breakpoint, running or stepping
+#[wasm_bindgen(js_name = toggleBreakpoint)]
+pub fn toggle_breakpoint(address: u16) -> Result<(), JsValue> {
+    control(Command::ToggleBreakpoint(address), |running| running.toggle_breakpoint(address))
+}
+
+#[wasm_bindgen]
+pub fn breakpoints() -> Result<Vec<u16>, JsValue> {
+    query(|status| status.breakpoints, |running| running.breakpoints())
+}
+
+// types the text into the KBD register a key at a time, each held down for cycles_per_key cycles and then
+// released for as many, e.g. for a program reading it with Keyboard.readLine; \n is Enter
+#[wasm_bindgen(js_name = typeText)]
+pub fn type_text(text: &str, cycles_per_key: Option<u32>) -> Result<(), JsValue> {
+    let cycles_per_key = cycles_per_key.unwrap_or(engine::CYCLES_PER_TYPED_KEY);
+    control(Command::TypeText(text.to_string(), cycles_per_key), |running| running.type_text(text, cycles_per_key))
+}
+
+// until the last key of the text is released
+#[wasm_bindgen(js_name = isTyping)]
+pub fn is_typing() -> Result<bool, JsValue> {
+    query(|status| status.typing, |running| running.is_typing())
+}
+
+// the RAM, registers and ROM into localStorage; with a worker, once the worker has sent them
+#[wasm_bindgen(js_name = saveSnapshot)]
+pub fn save_snapshot(slot: &str) -> Result<(), JsValue> {
+    if worker::is_host() {
+        return worker::send(Command::TakeSnapshot(slot.to_string())).map_err(|err| JsValue::from_str(&err.to_string()));
+    }
+    let text = with_running(|running| running.snapshot().to_text())?;
+    storage::save(slot, &text).map_err(|err| JsValue::from_str(&err.to_string()))
+}
+
+// false when nothing was saved in the slot
+#[wasm_bindgen(js_name = loadSnapshot)]
+pub fn load_snapshot(slot: &str) -> Result<bool, JsValue> {
+    let text = match storage::load(slot).map_err(|err| JsValue::from_str(&err.to_string()))? {
+        Some(text) => text,
+        None => return Ok(false),
+    };
+    let snapshot = Snapshot::from_text(&text).map_err(|err| JsValue::from_str(&err))?;
+    control(Command::Restore(text), |running| running.restore(&snapshot))?;
+    Ok(true)
+}
+
+// 0 outside the RAM; not with a worker, which only sends the words the debugger panel shows
+#[wasm_bindgen]
+pub fn ram(address: u16) -> Result<u16, JsValue> {
+    if worker::is_host() {
+        return Err(JsValue::from_str("the RAM can't be read from a computer running in a worker"));
+    }
+    with_running(|running| running.machine().get_ram(address))
+}
@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Result};
+use std::future::Future;
+use wasm_bindgen::{
+    closure::WasmClosure, prelude::Closure, JsCast,
+};
+use web_sys::{
+    CanvasRenderingContext2d, DedicatedWorkerGlobalScope, Document, HtmlCanvasElement, HtmlElement, Storage, Window,
+};
+
+// Straight taken from https://rustwasm.github.io/book/game-of-life/debugging.html
+#[allow(unused_macros)]
+macro_rules! log {
+    ( $( $t:tt )* ) => {
+        web_sys::console::log_1(&format!( $( $t )* ).into());
+    }
+}
+
+pub fn window() -> Result<Window> {
+    web_sys::window().ok_or_else(|| anyhow!("No Window Found"))
+}
+
+pub fn worker_scope() -> Result<DedicatedWorkerGlobalScope> {
+    js_sys::global()
+        .dyn_into::<DedicatedWorkerGlobalScope>()
+        .map_err(|_| anyhow!("Not running in a Worker"))
+}
+
+// the value of ?name=value in the address of the page or the worker, "" for ?name
+pub fn query_parameter(name: &str) -> Result<Option<String>> {
+    let search = match window() {
+        Ok(window) => window
+            .location()
+            .search()
+            .map_err(|err| anyhow!("Error reading the location {:#?}", err))?,
+        Err(_) => worker_scope()?.location().search(),
+    };
+    Ok(search.trim_start_matches('?').split('&').find_map(|parameter| {
+        let (key, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+        (key == name).then(|| value.to_string())
+    }))
+}
+
+// the #fragment of the page address, "" without one
+pub fn fragment() -> Result<String> {
+    window()?
+        .location()
+        .hash()
+        .map_err(|err| anyhow!("Error reading the location {:#?}", err))
+}
+
+// the page address without its #fragment
+pub fn page_address() -> Result<String> {
+    let href = window()?
+        .location()
+        .href()
+        .map_err(|err| anyhow!("Error reading the location {:#?}", err))?;
+    Ok(href.split('#').next().unwrap_or_default().to_string())
+}
+
+pub fn document() -> Result<Document> {
+    window()?
+        .document()
+        .ok_or_else(|| anyhow!("No Document Found"))
+}
+
+pub fn local_storage() -> Result<Storage> {
+    window()?
+        .local_storage()
+        .map_err(|err| anyhow!("Error getting localStorage {:#?}", err))?
+        .ok_or_else(|| anyhow!("No localStorage found"))
+}
+
+pub fn canvas() -> Result<HtmlCanvasElement> {
+    document()?
+        .get_element_by_id("canvas")
+        .ok_or_else(|| anyhow!("No Canvas Element found with ID 'canvas'"))?
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .map_err(|element| anyhow!("Error converting {:#?} to HtmlCanvasElement", element))
+}
+
+pub fn element(id: &str) -> Result<HtmlElement> {
+    document()?
+        .get_element_by_id(id)
+        .ok_or_else(|| anyhow!("No Element found with ID '{}'", id))?
+        .dyn_into::<web_sys::HtmlElement>()
+        .map_err(|element| anyhow!("Error converting {:#?} to HtmlElement", element))
+}
+
+pub fn context() -> Result<CanvasRenderingContext2d> {
+    canvas()?
+        .get_context("2d")
+        .map_err(|js_value| anyhow!("Error getting 2d context {:#?}", js_value))?
+        .ok_or_else(|| anyhow!("No 2d context found"))?
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .map_err(|element| {
+            anyhow!(
+                "Error converting {:#?} to CanvasRenderingContext2d",
+                element
+            )
+        })
+}
+
+pub fn spawn_local<F>(future: F)
+where
+    F: Future<Output = ()> + 'static,
+{
+    wasm_bindgen_futures::spawn_local(future);
+}
+
+pub type LoopClosure = Closure<dyn FnMut(f64)>;
+pub fn create_raf_closure(f: impl FnMut(f64) + 'static) -> LoopClosure {
+    closure_wrap(Box::new(f))
+}
+
+pub fn request_animation_frame(callback: &LoopClosure) -> Result<i32> {
+    window()?
+        .request_animation_frame(callback.as_ref().unchecked_ref())
+        .map_err(|err| anyhow!("Cannot request animation frame {:#?}", err))
+}
+
+pub fn closure_wrap<T: WasmClosure + ?Sized>(data: Box<T>) -> Closure<T> {
+    Closure::wrap(data)
+}
+
+pub type TimeoutClosure = Closure<dyn FnMut()>;
+pub fn set_timeout(callback: &TimeoutClosure, milliseconds: i32) -> Result<i32> {
+    worker_scope()?
+        .set_timeout_with_callback_and_timeout_and_arguments_0(callback.as_ref().unchecked_ref(), milliseconds)
+        .map_err(|err| anyhow!("Cannot set timeout {:#?}", err))
+}
+
+// in a page or in a worker
+pub fn now() -> Result<f64> {
+    let performance = match window() {
+        Ok(window) => window.performance(),
+        Err(_) => worker_scope()?.performance(),
+    };
+    Ok(performance
+        .ok_or_else(|| anyhow!("Performance object not found"))?
+        .now())
+}
@@ -0,0 +1,302 @@
+use anyhow::{anyhow, Result};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use hack_common::disassembler;
+use hack_common::machine::Machine;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, HtmlInputElement};
+
+use crate::browser;
+use crate::engine::Running;
+use crate::worker::{self, Command, Status};
+
+// a panel beside the canvas with the registers, a window on the RAM whose words can be edited,
+// the ROM around the PC where clicking an instruction sets a breakpoint, and Step and Run buttons;
+// with a worker, what is clicked goes to it as commands and it sends the windows back every frame
+
+const RAM_ROWS: u16 = 16;
+const ROM_ROWS: u16 = 16;
+const KBD: u16 = 24576;
+
+const CURRENT: &str = "white-space: pre; cursor: pointer; background: #cce0ff";
+const OTHER: &str = "white-space: pre; cursor: pointer";
+
+enum Action {
+    Run,
+    Step,
+    ShowRam(String),
+    // a row of the RAM window and what was typed in it
+    SetRam(u16, String),
+    // a row of the ROM window
+    ToggleBreakpoint(u16),
+}
+
+// the words the panel shows, read from the machine on the page or sent by the worker
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct Window {
+    pub ram_top: u16,
+    pub ram: Vec<u16>,
+    // ROM_ROWS from a few before the PC
+    pub rom_top: u16,
+    pub rom: Vec<u16>,
+}
+
+pub fn window(machine: &dyn Machine, ram_top: u16) -> Window {
+    let rom_top = machine.get_pc().saturating_sub(ROM_ROWS / 4).min(32768 - ROM_ROWS);
+    Window {
+        ram_top,
+        ram: (ram_top..ram_top + RAM_ROWS).map(|address| machine.get_ram(address)).collect(),
+        rom_top,
+        rom: (rom_top..rom_top + ROM_ROWS).map(|address| machine.get_rom(address)).collect(),
+    }
+}
+
+pub struct Debugger {
+    action_receiver: UnboundedReceiver<Action>,
+    registers: HtmlElement,
+    run: HtmlElement,
+    ram_addresses: Vec<HtmlElement>,
+    ram_values: Vec<HtmlInputElement>,
+    rom_rows: Vec<HtmlElement>,
+    // the RAM window asked for
+    ram_top: u16,
+    // the one shown, which a worker may not have caught up with
+    window: Window,
+}
+
+impl Debugger {
+    pub(crate) fn new() -> Result<Self> {
+        let (action_sender, action_receiver) = unbounded();
+        let panel = create("div", "display: inline-block; vertical-align: top; margin-left: 8px; font: 13px monospace")?;
+        browser::canvas()?
+            .insert_adjacent_element("afterend", &panel)
+            .map_err(|err| anyhow!("Error attaching the debugger {:#?}", err))?;
+
+        let buttons = append(&panel, "div", "")?;
+        let step = append(&buttons, "button", "")?;
+        step.set_text_content(Some("Step"));
+        on_click(&step, &action_sender, || Action::Step);
+        let run = append(&buttons, "button", "")?;
+        on_click(&run, &action_sender, || Action::Run);
+        let registers = append(&panel, "div", "white-space: pre; margin: 4px 0")?;
+
+        let ram_title = append(&panel, "div", "")?;
+        ram_title.set_text_content(Some("RAM from "));
+        let ram_top = input(&ram_title)?;
+        ram_top.set_value("0");
+        let text = ram_top.clone();
+        on_change(&ram_top, &action_sender, move || Action::ShowRam(text.value()));
+        let mut ram_addresses = Vec::new();
+        let mut ram_values = Vec::new();
+        for row in 0..RAM_ROWS {
+            let line = append(&panel, "div", "")?;
+            ram_addresses.push(append(&line, "span", "white-space: pre")?);
+            let value = input(&line)?;
+            let text = value.clone();
+            on_change(&value, &action_sender, move || Action::SetRam(row, text.value()));
+            ram_values.push(value);
+        }
+
+        let rom_title = append(&panel, "div", "margin-top: 4px")?;
+        rom_title.set_text_content(Some("ROM, click for a breakpoint"));
+        let mut rom_rows = Vec::new();
+        for row in 0..ROM_ROWS {
+            let line = append(&panel, "div", OTHER)?;
+            on_click(&line, &action_sender, move || Action::ToggleBreakpoint(row));
+            rom_rows.push(line);
+        }
+
+        Ok(Debugger {
+            action_receiver,
+            registers,
+            run,
+            ram_addresses,
+            ram_values,
+            rom_rows,
+            ram_top: 0,
+            window: Window::default(),
+        })
+    }
+
+    // applies what was clicked and typed since the last frame, then shows the machine
+    pub(crate) fn update(&mut self, running: &mut Running) {
+        while let Ok(action) = self.action_receiver.try_recv() {
+            if let Some(command) = self.command(action, running.is_paused()) {
+                worker::apply(running, command);
+            }
+        }
+        let window = window(running.machine(), self.ram_top);
+        self.show(&worker::status(running), window);
+    }
+
+    // as update, for a computer running in a worker
+    pub(crate) fn update_from_worker(&mut self) -> Result<()> {
+        while let Ok(action) = self.action_receiver.try_recv() {
+            if let Some(command) = self.command(action, worker::status_of_worker()?.paused) {
+                worker::send(command)?;
+            }
+        }
+        self.show(&worker::status_of_worker()?, worker::window_of_worker()?);
+        Ok(())
+    }
+
+    fn command(&mut self, action: Action, paused: bool) -> Option<Command> {
+        match action {
+            Action::Run if paused => Some(Command::Resume),
+            Action::Run => Some(Command::Pause),
+            Action::Step => Some(Command::Step(1)),
+            Action::ShowRam(text) => {
+                self.ram_top = parse_word(&text)?.min(KBD + 1 - RAM_ROWS);
+                Some(Command::ShowRam(self.ram_top))
+            }
+            Action::SetRam(row, text) => Some(Command::SetRam(self.window.ram_top + row, parse_word(&text)?)),
+            Action::ToggleBreakpoint(row) => Some(Command::ToggleBreakpoint(self.window.rom_top + row)),
+        }
+    }
+
+    fn show(&mut self, status: &Status, window: Window) {
+        set_text(&self.run, if status.paused { "Run" } else { "Pause" });
+        set_text(
+            &self.registers,
+            &format!("PC {:5}  A {:5}  D {:6}", status.pc, status.a, status.d as i16),
+        );
+
+        let focused = browser::document().ok().and_then(|document| document.active_element());
+        for (row, (address, value)) in self.ram_addresses.iter().zip(&self.ram_values).enumerate() {
+            let ram_address = window.ram_top + row as u16;
+            set_text(address, &format!("{:5} ", ram_address));
+            value.set_read_only(ram_address >= KBD);
+            // not while it is being edited
+            if focused.as_ref() != Some(value.as_ref()) {
+                let text = (window.ram.get(row).copied().unwrap_or(0) as i16).to_string();
+                if value.value() != text {
+                    value.set_value(&text);
+                }
+            }
+        }
+
+        for (row, line) in self.rom_rows.iter().enumerate() {
+            let address = window.rom_top + row as u16;
+            let word = window.rom.get(row).copied().unwrap_or(0);
+            let instruction = disassembler::disassemble(word).unwrap_or_else(|| "???".to_string());
+            let marker = if address == status.pc { '>' } else { ' ' };
+            let breakpoint = if status.breakpoints.contains(&address) { '●' } else { ' ' };
+            set_text(line, &format!("{}{}{:5} {}", marker, breakpoint, address, instruction));
+            let style = if address == status.pc { CURRENT } else { OTHER };
+            if line.get_attribute("style").as_deref() != Some(style) {
+                let _ = line.set_attribute("style", style);
+            }
+        }
+        self.window = window;
+    }
+}
+
+// a word as typed in the RAM window: -32768 to 65535, or 0x in hex or 0b in binary
+fn parse_word(text: &str) -> Option<u16> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix("0x") {
+        return u16::from_str_radix(hex, 16).ok();
+    }
+    if let Some(binary) = text.strip_prefix("0b") {
+        return u16::from_str_radix(binary, 2).ok();
+    }
+    match text.parse::<i32>() {
+        Ok(value) if (-32768..=65535).contains(&value) => Some(value as u16),
+        _ => None,
+    }
+}
+
+// the DOM is only touched when the text changes
+fn set_text(element: &HtmlElement, text: &str) {
+    if element.text_content().as_deref() != Some(text) {
+        element.set_text_content(Some(text));
+    }
+}
+
+fn create(tag: &str, style: &str) -> Result<HtmlElement> {
+    let element = browser::document()?
+        .create_element(tag)
+        .map_err(|err| anyhow!("Error creating {} {:#?}", tag, err))?
+        .dyn_into::<HtmlElement>()
+        .map_err(|element| anyhow!("Error converting {:#?} to HtmlElement", element))?;
+    if !style.is_empty() {
+        element
+            .set_attribute("style", style)
+            .map_err(|err| anyhow!("Error styling {} {:#?}", tag, err))?;
+    }
+    Ok(element)
+}
+
+fn append(parent: &Element, tag: &str, style: &str) -> Result<HtmlElement> {
+    let element = create(tag, style)?;
+    parent
+        .append_child(&element)
+        .map_err(|err| anyhow!("Error appending {} {:#?}", tag, err))?;
+    Ok(element)
+}
+
+fn input(parent: &Element) -> Result<HtmlInputElement> {
+    append(parent, "input", "width: 7ch; font: inherit")?
+        .dyn_into::<HtmlInputElement>()
+        .map_err(|element| anyhow!("Error converting {:#?} to HtmlInputElement", element))
+}
+
+fn on_click(element: &HtmlElement, action_sender: &UnboundedSender<Action>, action: impl Fn() -> Action + 'static) {
+    let action_sender = action_sender.clone();
+    let onclick = browser::closure_wrap(Box::new(move || {
+        let _ = action_sender.unbounded_send(action());
+    }) as Box<dyn FnMut()>);
+    element.set_onclick(Some(onclick.as_ref().unchecked_ref()));
+    onclick.forget();
+}
+
+// when a value is entered, on Enter or leaving the field
+fn on_change(element: &HtmlElement, action_sender: &UnboundedSender<Action>, action: impl Fn() -> Action + 'static) {
+    let action_sender = action_sender.clone();
+    let onchange = browser::closure_wrap(Box::new(move || {
+        let _ = action_sender.unbounded_send(action());
+    }) as Box<dyn FnMut()>);
+    element.set_onchange(Some(onchange.as_ref().unchecked_ref()));
+    onchange.forget();
+}
+
+#[cfg(test)]
+mod tests {
+    use hack_common::machine::Backend;
+    use rstest::*;
+    use super::*;
+
+    #[rstest]
+    #[case("17", Some(17))]
+    #[case(" -1 ", Some(65535))]
+    #[case("-32768", Some(32768))]
+    #[case("65535", Some(65535))]
+    #[case("0x4000", Some(16384))]
+    #[case("0b101", Some(5))]
+    #[case("65536", None)]
+    #[case("-32769", None)]
+    #[case("0xg", None)]
+    #[case("", None)]
+    fn test_parse_word(#[case] input: &str, #[case] output: Option<u16>) {
+        assert_eq!(output, parse_word(input));
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(10, 6)]
+    #[case(32767, 32752)]
+    fn test_window(#[case] pc: u16, #[case] rom_top: u16) {
+        let mut machine = Backend::Emulator.create();
+        machine.load_program(vec!["0000000000010001", "1110110000010000"]);
+        machine.set_registers(pc, 0, 0);
+        machine.set_ram(100, 7);
+        machine.set_ram(115, 65535);
+
+        let window = window(machine.as_ref(), 100);
+        assert_eq!((100, 16, 7, 65535, 0), (window.ram_top, window.ram.len(), window.ram[0], window.ram[15], window.ram[1]));
+        assert_eq!((rom_top, 16), (window.rom_top, window.rom.len()));
+        let program = [17, 0b1110_1100_0001_0000];
+        let words: Vec<u16> = (rom_top..rom_top + 16).map(|address| program.get(address as usize).copied().unwrap_or(0)).collect();
+        assert_eq!(words, window.rom);
+    }
+}
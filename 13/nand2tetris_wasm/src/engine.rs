@@ -0,0 +1,529 @@
+use crate::api;
+use crate::browser::{self, LoopClosure};
+use crate::debugger::Debugger;
+use crate::storage;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::channel::{
+    mpsc::{unbounded, UnboundedReceiver},
+};
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{CanvasRenderingContext2d, ImageData};
+use hack_common::machine::ResetMode;
+use hack_common::keyboard::{self, PressedKeys, TypedText};
+use hack_common::link;
+use hack_common::machine::Machine;
+use hack_common::snapshot::Snapshot;
+
+const SCREEN_WIDTH: usize = 512;
+const SCREEN_HEIGHT: usize = 256;
+
+// the screen in RGBA, put on the canvas once a frame when something was drawn;
+// the canvas is 512x256 and scaled up by CSS
+pub struct Renderer {
+    context: CanvasRenderingContext2d,
+    pixels: Vec<u8>,
+    changed: bool,
+}
+
+impl Renderer {
+    pub(crate) fn new(context: CanvasRenderingContext2d) -> Self {
+        Renderer {
+            context,
+            pixels: vec![255; SCREEN_WIDTH * SCREEN_HEIGHT * 4],
+            changed: true,
+        }
+    }
+
+    pub fn draw_pixel(&mut self, x: i32, y: i32, black: bool) {
+        let i = (y as usize * SCREEN_WIDTH + x as usize) * 4;
+        self.pixels[i..i + 3].fill(if black { 0 } else { 255 });
+        self.changed = true;
+    }
+
+    // the whole screen, a pixel per element from the top left
+    pub fn draw_screen(&mut self, screen: &[bool]) {
+        for (pixel, black) in self.pixels.chunks_exact_mut(4).zip(screen) {
+            let value = if *black { 0 } else { 255 };
+            if pixel[0] != value {
+                pixel[..3].fill(value);
+                self.changed = true;
+            }
+        }
+    }
+
+    // the screen as the 8192 words of the Hack RAM, the lowest bit leftmost
+    pub fn draw_words(&mut self, words: &[u16]) {
+        for (pixels, word) in self.pixels.chunks_exact_mut(16 * 4).zip(words) {
+            for (bit, pixel) in pixels.chunks_exact_mut(4).enumerate() {
+                pixel[..3].fill(if word >> bit & 1 == 1 { 0 } else { 255 });
+            }
+        }
+        self.changed = true;
+    }
+
+    pub(crate) fn present(&mut self) -> Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+        let image = ImageData::new_with_u8_clamped_array(Clamped(&self.pixels), SCREEN_WIDTH as u32)
+            .map_err(|js_value| anyhow!("Error creating ImageData {:#?}", js_value))?;
+        self.context
+            .put_image_data(&image, 0.0, 0.0)
+            .map_err(|js_value| anyhow!("Error putting ImageData {:#?}", js_value))?;
+        self.changed = false;
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+pub trait ComputerSystem {
+    async fn initialize(&mut self, keystate: &KeyState) -> Result<Box<dyn ComputerSystem>>;
+    fn update(&mut self, keystate: &KeyState);
+    fn run(&mut self, cycles: u32, keystate: &KeyState) {
+        for _ in 0..cycles {
+            self.update(keystate);
+        }
+    }
+    fn reset(&mut self, reset_mode: ResetMode, keystate: &KeyState);
+    fn machine(&mut self) -> &mut dyn Machine;
+    // the milliseconds a frame runs cycles_per_frame in
+    fn frame_size(&self) -> f32;
+    fn draw(&mut self, renderer: &mut Renderer);
+}
+
+// Sixty Frames per second, converted to a frame length in milliseconds
+pub(crate) const FRAME_SIZE: f32 = 1.0 / 60.0 * 1000.0;
+pub struct ComputerSystemLoop {
+    last_frame: f64,
+    accumulated_delta: f32,
+}
+// by default the loop runs as many cycles as fit in this many milliseconds a frame,
+// leaving the rest of a 60 Hz frame to draw
+const TIME_BUDGET: f64 = 8.0;
+// how long a typed key is held down, and then released, by default;
+// long enough for Keyboard.readLine to print a character between keys
+pub const CYCLES_PER_TYPED_KEY: u32 = 50000;
+type SharedLoopClosure = Rc<RefCell<Option<LoopClosure>>>;
+
+// the computer system the loop runs, shared with the functions exported to JavaScript
+pub struct Running {
+    computer_system: Box<dyn ComputerSystem>,
+    keystate: KeyState,
+    paused: bool,
+    cycles_per_frame: u32,
+    // Some runs for a time instead of cycles_per_frame
+    time_budget: Option<f64>,
+    // the cycles run between looking at the clock
+    batch: u32,
+    throughput: Throughput,
+    // addresses in the ROM that pause the computer when the PC gets to them
+    breakpoints: BTreeSet<u16>,
+    // text being typed into the KBD register
+    typing: Option<TypedText>,
+}
+
+// the cycles run per second, measured over a second
+struct Throughput {
+    since: f64,
+    cycles: u64,
+    per_second: f64,
+}
+
+impl Throughput {
+    fn new(now: f64) -> Self {
+        Throughput {
+            since: now,
+            cycles: 0,
+            per_second: 0.0,
+        }
+    }
+
+    fn update(&mut self, now: f64) {
+        let elapsed = now - self.since;
+        if elapsed >= 1000.0 {
+            self.per_second = self.cycles as f64 * 1000.0 / elapsed;
+            self.cycles = 0;
+            self.since = now;
+        }
+    }
+}
+
+thread_local! {
+    static RUNNING: RefCell<Option<Running>> = const { RefCell::new(None) };
+}
+
+pub(crate) async fn start_running(mut computer_system: impl ComputerSystem + 'static, now: f64) -> Result<()> {
+    let keystate = KeyState::new();
+    let computer_system = computer_system.initialize(&keystate).await?;
+    RUNNING.with(|running| {
+        *running.borrow_mut() = Some(Running {
+            computer_system,
+            keystate,
+            paused: false,
+            cycles_per_frame: 1,
+            time_budget: Some(TIME_BUDGET),
+            batch: 1,
+            throughput: Throughput::new(now),
+            breakpoints: BTreeSet::new(),
+            typing: None,
+        })
+    });
+    Ok(())
+}
+
+pub fn with_running<T>(f: impl FnOnce(&mut Running) -> T) -> Result<T> {
+    RUNNING.with(|running| {
+        let mut running = running
+            .try_borrow_mut()
+            .map_err(|_| anyhow!("ComputerSystemLoop: Running is in use"))?;
+        running
+            .as_mut()
+            .map(f)
+            .ok_or_else(|| anyhow!("ComputerSystemLoop: Not started yet"))
+    })
+}
+
+impl Running {
+    pub fn machine(&mut self) -> &mut dyn Machine {
+        self.computer_system.machine()
+    }
+
+    // a new program starts from a cleared RAM
+    pub fn load_program(&mut self, instructions: &[String]) {
+        self.machine().load_program(instructions.iter().map(String::as_str).collect());
+        self.reset(ResetMode::PowerCycle);
+    }
+
+    // the .hack text of the program in the ROM
+    pub fn program_text(&mut self) -> String {
+        link::hack_text(&self.machine().get_rom_words())
+    }
+
+    pub fn snapshot(&mut self) -> Snapshot {
+        Snapshot::take(self.machine())
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        snapshot.restore(self.machine());
+    }
+
+    pub fn reset(&mut self, reset_mode: ResetMode) {
+        self.computer_system.reset(reset_mode, &self.keystate);
+    }
+
+    // stops early, paused, at a breakpoint
+    pub fn step(&mut self, cycles: u32) {
+        if self.breakpoints.is_empty() && self.typing.is_none() {
+            self.computer_system.run(cycles, &self.keystate);
+            self.throughput.cycles += u64::from(cycles);
+            return;
+        }
+        for _ in 0..cycles {
+            if let Some(typing) = &mut self.typing {
+                let code = typing.next_key();
+                self.keystate.set_typed(code.unwrap_or(0));
+                if code.is_none() {
+                    self.typing = None;
+                }
+            }
+            self.computer_system.update(&self.keystate);
+            self.throughput.cycles += 1;
+            let pc = self.machine().get_pc();
+            if self.is_breakpoint(pc) {
+                self.paused = true;
+                break;
+            }
+        }
+    }
+
+    // a frame of a computer running in a worker, which has no requestAnimationFrame
+    pub(crate) fn run_worker_frame(&mut self, now: f64) {
+        if !self.paused {
+            match self.time_budget {
+                Some(time_budget) => {
+                    let _ = self.run_for(time_budget);
+                }
+                None => self.step(self.cycles_per_frame),
+            }
+        }
+        self.throughput.update(now);
+    }
+
+    pub(crate) fn frame_size(&self) -> f32 {
+        self.computer_system.frame_size()
+    }
+
+    // the key the page sends to a computer running in a worker
+    pub(crate) fn set_key_code(&mut self, code: u16) {
+        self.keystate.set_code(code);
+    }
+
+    fn run_for(&mut self, time_budget: f64) -> Result<()> {
+        let start = browser::now()?;
+        let mut cycles = 0;
+        while !self.paused && browser::now()? - start < time_budget {
+            self.step(self.batch);
+            cycles += self.batch;
+        }
+        if self.paused {
+            return Ok(());
+        }
+        // about ten batches a frame, so the budget is overrun by a tenth at most
+        self.batch = (cycles / 10).max(1);
+        Ok(())
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_speed(&mut self, cycles_per_frame: u32) {
+        self.cycles_per_frame = cycles_per_frame.max(1);
+        self.time_budget = None;
+    }
+
+    pub fn speed(&self) -> u32 {
+        self.cycles_per_frame
+    }
+
+    // milliseconds a frame, or 0 to go back to cycles_per_frame
+    pub fn set_time_budget(&mut self, time_budget: f64) {
+        self.time_budget = Some(time_budget).filter(|time_budget| *time_budget > 0.0);
+    }
+
+    pub fn time_budget(&self) -> f64 {
+        self.time_budget.unwrap_or(0.0)
+    }
+
+    pub fn instructions_per_second(&self) -> f64 {
+        self.throughput.per_second
+    }
+
+    // in place of any text still being typed
+    pub fn type_text(&mut self, text: &str, cycles_per_key: u32) {
+        self.typing = Some(TypedText::new(text, cycles_per_key));
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.typing.is_some()
+    }
+
+    pub fn toggle_breakpoint(&mut self, address: u16) {
+        if !self.breakpoints.remove(&address) {
+            self.breakpoints.insert(address);
+        }
+    }
+
+    pub fn is_breakpoint(&self, address: u16) -> bool {
+        self.breakpoints.contains(&address)
+    }
+
+    pub fn breakpoints(&self) -> Vec<u16> {
+        self.breakpoints.iter().copied().collect()
+    }
+}
+
+impl ComputerSystemLoop {
+    pub async fn start(computer_system: impl ComputerSystem + 'static) -> Result<()> {
+        let mut keyevent_receiver = prepare_input()?;
+        let mut reset_receiver = prepare_reset()?;
+
+        let mut computer_system_loop = ComputerSystemLoop {
+            last_frame: browser::now()?,
+            accumulated_delta: 0.0,
+        };
+
+        let mut renderer = Renderer::new(browser::context()?);
+        let mut debugger = Debugger::new()?;
+
+        let f: SharedLoopClosure = Rc::new(RefCell::new(None));
+        let g = f.clone();
+
+        start_running(computer_system, computer_system_loop.last_frame).await?;
+        // a program shared as a link, or else where the page was left, if the browser lets it keep anything
+        if !load_link_of_page() {
+            if let Ok(Some(text)) = storage::load(storage::AUTOSAVE) {
+                match Snapshot::from_text(&text) {
+                    Ok(snapshot) => with_running(|running| running.restore(&snapshot))?,
+                    Err(err) => {
+                        log!("Could not restore the autosave: {}", err);
+                    }
+                }
+            }
+        }
+        storage::autosave_on_page_hide(|| with_running(|running| running.snapshot().to_text()).ok())?;
+        *g.borrow_mut() = Some(browser::create_raf_closure(move |perf: f64| {
+            let _ = with_running(|running| {
+                process_input(&mut running.keystate, &mut keyevent_receiver);
+                while let Ok(reset_mode) = reset_receiver.try_recv() {
+                    running.reset(reset_mode);
+                }
+
+                computer_system_loop.accumulated_delta += (perf - computer_system_loop.last_frame) as f32;
+                // no catching up on the frames missed while paused
+                if running.paused {
+                    computer_system_loop.accumulated_delta = 0.0;
+                } else if let Some(time_budget) = running.time_budget {
+                    computer_system_loop.accumulated_delta = 0.0;
+                    let _ = running.run_for(time_budget);
+                }
+                //log!("start {}", computer_system_loop.accumulated_delta.to_string());
+                let frame_size = running.frame_size();
+                while !running.paused && computer_system_loop.accumulated_delta > frame_size {
+                    //log!("in {}", computer_system_loop.accumulated_delta.to_string());
+                    running.step(running.cycles_per_frame);
+                    //log!("in2 {}", computer_system_loop.accumulated_delta.to_string());
+                    computer_system_loop.accumulated_delta -= frame_size;
+                }
+                //log!("end {}", computer_system_loop.accumulated_delta.to_string());
+                computer_system_loop.last_frame = perf;
+                running.throughput.update(perf);
+                running.computer_system.draw(&mut renderer);
+                let _ = renderer.present();
+                debugger.update(running);
+            });
+
+            let _ = browser::request_animation_frame(f.borrow().as_ref().unwrap());
+        }));
+
+        let _ = browser::request_animation_frame(
+            g.borrow()
+                .as_ref()
+                .ok_or_else(|| anyhow!("ComputerSystemLoop: Loop is None"))?,
+        )?;
+        Ok(())
+    }
+}
+
+// true when the page address had a program, even one that couldn't be loaded
+pub(crate) fn load_link_of_page() -> bool {
+    let fragment = browser::fragment().unwrap_or_default();
+    match api::load_program_link(&fragment) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            log!("Could not load the program of the link: {:?}", err.as_string());
+            true
+        }
+    }
+}
+
+pub struct KeyState {
+    pressed_keys: PressedKeys,
+}
+
+// the key code a worker gets from the page, in place of a key of its own
+const PAGE_KEY: &str = "Page";
+// the key of the text being typed
+const TYPED_KEY: &str = "Typed";
+
+impl KeyState {
+    pub(crate) fn new() -> Self {
+        KeyState {
+            pressed_keys: PressedKeys::new(),
+        }
+    }
+
+    // the Hack key code of the KBD register
+    pub fn code(&self) -> u16 {
+        self.pressed_keys.code()
+    }
+
+    // event.key is the typed character of the user's keyboard layout
+    fn set_pressed(&mut self, code: &str, event: web_sys::KeyboardEvent) {
+        if event.ctrl_key() || event.meta_key() {
+            return;
+        }
+        if let Some(key_code) = keyboard::key_code(&event.key()) {
+            self.pressed_keys.press(code, key_code);
+        }
+    }
+
+    fn set_released(&mut self, code: &str) {
+        self.pressed_keys.release(code);
+    }
+
+    fn set_code(&mut self, code: u16) {
+        self.hold(PAGE_KEY, code);
+    }
+
+    fn set_typed(&mut self, code: u16) {
+        self.hold(TYPED_KEY, code);
+    }
+
+    // 0 releases the key
+    fn hold(&mut self, key: &str, code: u16) {
+        self.pressed_keys.release(key);
+        if code != 0 {
+            self.pressed_keys.press(key, code);
+        }
+    }
+}
+
+pub(crate) enum KeyPress {
+    KeyUp(web_sys::KeyboardEvent),
+    KeyDown(web_sys::KeyboardEvent),
+}
+
+pub(crate) fn process_input(state: &mut KeyState, keyevent_receiver: &mut UnboundedReceiver<KeyPress>) {
+    while let Ok(evt) = keyevent_receiver.try_recv() {
+        match evt {
+            KeyPress::KeyUp(evt) => state.set_released(&evt.code()),
+            KeyPress::KeyDown(evt) => state.set_pressed(&evt.code(), evt),
+        }
+    }
+}
+
+pub(crate) fn prepare_input() -> Result<UnboundedReceiver<KeyPress>> {
+    let (keydown_sender, keyevent_receiver) = unbounded();
+    let keydown_sender = Rc::new(RefCell::new(keydown_sender));
+    let keyup_sender = Rc::clone(&keydown_sender);
+    let onkeydown = browser::closure_wrap(Box::new(move |keycode: web_sys::KeyboardEvent| {
+        // keys of the Hack keyboard should not scroll or navigate the page
+        if !(keycode.ctrl_key() || keycode.meta_key()) && keyboard::key_code(&keycode.key()).is_some() {
+            keycode.prevent_default();
+        }
+        let _ = keydown_sender
+            .borrow_mut()
+            .start_send(KeyPress::KeyDown(keycode));
+    }) as Box<dyn FnMut(web_sys::KeyboardEvent)>);
+
+    let onkeyup = browser::closure_wrap(Box::new(move |keycode: web_sys::KeyboardEvent| {
+        let _ = keyup_sender
+            .borrow_mut()
+            .start_send(KeyPress::KeyUp(keycode));
+    }) as Box<dyn FnMut(web_sys::KeyboardEvent)>);
+
+    browser::canvas()?.set_onkeydown(Some(onkeydown.as_ref().unchecked_ref()));
+    browser::canvas()?.set_onkeyup(Some(onkeyup.as_ref().unchecked_ref()));
+    onkeydown.forget();
+    onkeyup.forget();
+
+    Ok(keyevent_receiver)
+}
+
+// the Reset and Power cycle buttons under the canvas
+pub(crate) fn prepare_reset() -> Result<UnboundedReceiver<ResetMode>> {
+    let (reset_sender, reset_receiver) = unbounded();
+    for (id, reset_mode) in [("reset", ResetMode::Pc), ("power-cycle", ResetMode::PowerCycle)] {
+        let mut reset_sender = reset_sender.clone();
+        let onclick = browser::closure_wrap(Box::new(move || {
+            let _ = reset_sender.start_send(reset_mode);
+            // give the keyboard back to the computer
+            let _ = browser::canvas().map(|canvas| canvas.focus());
+        }) as Box<dyn FnMut()>);
+        browser::element(id)?.set_onclick(Some(onclick.as_ref().unchecked_ref()));
+        onclick.forget();
+    }
+
+    Ok(reset_receiver)
+}
@@ -1,7 +1,11 @@
+mod api;
 #[macro_use]
 mod browser;
+mod debugger;
 mod engine;
 mod nand2tetris;
+mod storage;
+mod worker;
 
 use engine::ComputerSystemLoop;
 use nand2tetris::Nand2Tetris;
@@ -12,8 +16,17 @@ use wasm_bindgen::prelude::*;
 pub fn main_js() -> Result<(), JsValue> {
     console_error_panic_hook::set_once();
 
+    // a worker imports the module too, and starts with runWorker
+    if browser::worker_scope().is_ok() {
+        return Ok(());
+    }
+    let backend = nand2tetris::backend().map_err(|err| JsValue::from_str(&err.to_string()))?;
+    if browser::query_parameter("worker").unwrap_or(None).is_some() {
+        return worker::start_host(backend).map_err(|err| JsValue::from_str(&err.to_string()));
+    }
+
     browser::spawn_local(async move {
-        let nand2tetris = Nand2Tetris::new();
+        let nand2tetris = Nand2Tetris::new(backend);
 
         ComputerSystemLoop::start(nand2tetris)
             .await
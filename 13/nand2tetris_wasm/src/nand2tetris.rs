@@ -0,0 +1,75 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use hack_common::machine::{Backend, Machine, ResetMode};
+use hack_common::program;
+
+use crate::{
+    browser,
+    engine::{ComputerSystem, KeyState, Renderer, FRAME_SIZE},
+};
+
+// ?backend=emulator|builtin|gates in the address of the page or the worker, the emulator by default
+pub fn backend() -> Result<Backend> {
+    match browser::query_parameter("backend")? {
+        Some(name) => name.parse().map_err(|err: String| anyhow!(err)),
+        None => Ok(Backend::default()),
+    }
+}
+
+pub struct Nand2Tetris {
+    backend: Backend,
+    computer: Box<dyn Machine>,
+}
+
+impl Nand2Tetris {
+    pub fn new(backend: Backend) -> Self {
+        let mut computer = backend.create();
+        computer.load_program(program::FILL.to_vec());
+        Nand2Tetris {
+            backend,
+            computer,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl ComputerSystem for Nand2Tetris {
+    async fn initialize(&mut self, keystate: &KeyState) -> Result<Box<dyn ComputerSystem>> {
+        let mut nand2tetris = Box::new(Nand2Tetris::new(self.backend));
+        nand2tetris.computer.step(true, keystate.code());
+        Ok(nand2tetris)
+    }
+
+    fn update(&mut self, keystate: &KeyState) {
+        self.computer.step(false, keystate.code());
+    }
+
+    fn reset(&mut self, reset_mode: ResetMode, keystate: &KeyState) {
+        self.computer.set_reset_mode(reset_mode);
+        self.computer.step(true, keystate.code());
+    }
+
+    fn machine(&mut self) -> &mut dyn Machine {
+        self.computer.as_mut()
+    }
+
+    fn frame_size(&self) -> f32 {
+        // a cycle down to the Nand gates takes long
+        if self.backend == Backend::Gates {
+            1.0 / 5.0 * 1000.0
+        } else {
+            FRAME_SIZE
+        }
+    }
+
+    fn draw(&mut self, renderer: &mut Renderer) {
+        match self.computer.get_update_screen_pixels() {
+            Some(pixels) => {
+                for (x, y, color) in pixels {
+                    renderer.draw_pixel(x, y, color);
+                }
+            }
+            None => renderer.draw_screen(self.computer.screen()),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use anyhow::{anyhow, Result};
+use wasm_bindgen::JsCast;
+
+use crate::browser;
+
+// snapshots of the machine kept in localStorage as text, a key a slot
+const KEY_PREFIX: &str = "nand2tetris.snapshot.";
+
+// the slot written when the page goes away and restored when it starts again
+pub const AUTOSAVE: &str = "autosave";
+
+pub fn save(slot: &str, text: &str) -> Result<()> {
+    browser::local_storage()?
+        .set_item(&format!("{}{}", KEY_PREFIX, slot), text)
+        .map_err(|err| anyhow!("Error saving the snapshot {:#?}", err))
+}
+
+// None for a slot never saved
+pub fn load(slot: &str) -> Result<Option<String>> {
+    browser::local_storage()?
+        .get_item(&format!("{}{}", KEY_PREFIX, slot))
+        .map_err(|err| anyhow!("Error loading the snapshot {:#?}", err))
+}
+
+// pagehide rather than unload, which isn't sent to pages kept in the back/forward cache;
+// snapshot_text is None when there is nothing to save yet
+pub(crate) fn autosave_on_page_hide(snapshot_text: impl Fn() -> Option<String> + 'static) -> Result<()> {
+    let onpagehide = browser::closure_wrap(Box::new(move || {
+        if let Some(text) = snapshot_text() {
+            if let Err(err) = save(AUTOSAVE, &text) {
+                log!("Could not autosave {:#?}", err);
+            }
+        }
+    }) as Box<dyn FnMut()>);
+    browser::window()?
+        .add_event_listener_with_callback("pagehide", onpagehide.as_ref().unchecked_ref())
+        .map_err(|err| anyhow!("Error listening for pagehide {:#?}", err))?;
+    onpagehide.forget();
+    Ok(())
+}
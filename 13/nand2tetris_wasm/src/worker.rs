@@ -0,0 +1,376 @@
+use anyhow::{anyhow, Result};
+use futures::channel::mpsc::unbounded;
+use hack_common::link;
+use hack_common::machine::{Backend, ResetMode};
+use hack_common::snapshot::Snapshot;
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{MessageEvent, Worker};
+
+use crate::browser::{self, LoopClosure, TimeoutClosure};
+use crate::debugger::{self, Debugger, Window};
+use crate::engine::{self, KeyState, Renderer, Running};
+use crate::nand2tetris::{self, Nand2Tetris};
+use crate::storage;
+
+// With ?worker in the page address the computer runs in a Web Worker and the page only draws.
+// The page sends commands as JSON and the worker answers every frame with a Frame.
+
+#[derive(Serialize, Deserialize)]
+pub enum Command {
+    Key(u16),
+    Reset,
+    PowerCycle,
+    Load(Vec<String>),
+    Pause,
+    Resume,
+    Step(u32),
+    SetSpeed(u32),
+    SetTimeBudget(f64),
+    SetRam(u16, u16),
+    ToggleBreakpoint(u16),
+    // the text and the cycles a key is held down
+    TypeText(String, u32),
+    // the worker sends the snapshot back in a frame, for the page to save in the slot
+    TakeSnapshot(String),
+    Restore(String),
+    // the first address of the RAM window the debugger panel shows
+    ShowRam(u16),
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Status {
+    pub pc: u16,
+    pub a: u16,
+    pub d: u16,
+    pub paused: bool,
+    pub speed: u32,
+    pub time_budget: f64,
+    pub instructions_per_second: f64,
+    pub breakpoints: Vec<u16>,
+    pub typing: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Frame {
+    status: Status,
+    // the screen as 8192 words, when it changed
+    screen: Option<Vec<u16>>,
+    // the slot and text of a snapshot asked for
+    snapshot: Option<(String, String)>,
+    // a snapshot every AUTOSAVE_INTERVAL, for the page to save when it goes away
+    autosave: Option<String>,
+    // for the debugger panel
+    window: Window,
+}
+
+// nothing else runs in the worker, so a frame may take most of 1/60 s
+const WORKER_TIME_BUDGET: f64 = 15.0;
+
+// in ms; pagehide can't wait for the worker, so the page keeps the last one
+const AUTOSAVE_INTERVAL: f64 = 1000.0;
+
+#[wasm_bindgen(js_name = runWorker)]
+pub fn run_worker() {
+    browser::spawn_local(async move {
+        if let Err(err) = start_worker().await {
+            log!("Could not start the worker {:#?}", err);
+        }
+    });
+}
+
+async fn start_worker() -> Result<()> {
+    let scope = browser::worker_scope()?;
+    let (command_sender, mut command_receiver) = unbounded();
+    let onmessage = browser::closure_wrap(Box::new(move |event: MessageEvent| {
+        if let Some(command) = event.data().as_string().and_then(|data| serde_json::from_str(&data).ok()) {
+            let _ = command_sender.unbounded_send(command);
+        }
+    }) as Box<dyn FnMut(MessageEvent)>);
+    scope.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    onmessage.forget();
+
+    engine::start_running(Nand2Tetris::new(nand2tetris::backend()?), browser::now()?).await?;
+    engine::with_running(|running| running.set_time_budget(WORKER_TIME_BUDGET))?;
+
+    let f: Rc<RefCell<Option<TimeoutClosure>>> = Rc::new(RefCell::new(None));
+    let g = f.clone();
+    let mut last_screen = Vec::new();
+    let mut ram_top = 0;
+    // the first a second after the start, once the page's commands, a restored autosave among them, are applied
+    let mut last_autosave = browser::now()?;
+    *g.borrow_mut() = Some(browser::closure_wrap(Box::new(move || {
+        let delay = engine::with_running(|running| {
+            let mut snapshot = None;
+            while let Ok(command) = command_receiver.try_recv() {
+                match command {
+                    Command::TakeSnapshot(slot) => snapshot = Some((slot, running.snapshot().to_text())),
+                    Command::ShowRam(address) => ram_top = address,
+                    command => apply(running, command),
+                }
+            }
+            let now = browser::now().unwrap_or_default();
+            running.run_worker_frame(now);
+
+            let screen = screen_words(running.machine().screen());
+            let autosave = (now - last_autosave >= AUTOSAVE_INTERVAL).then(|| running.snapshot().to_text());
+            if autosave.is_some() {
+                last_autosave = now;
+            }
+            let frame = Frame {
+                status: status(running),
+                screen: (screen != last_screen).then(|| screen.clone()),
+                snapshot,
+                autosave,
+                window: debugger::window(running.machine(), ram_top),
+            };
+            last_screen = screen;
+            if let Ok(frame) = serde_json::to_string(&frame) {
+                let _ = scope.post_message(&frame.into());
+            }
+            // back to back when running for a time budget, else at the pace of the page
+            if running.time_budget() > 0.0 { 0 } else { running.frame_size() as i32 }
+        })
+        .unwrap_or(engine::FRAME_SIZE as i32);
+        let _ = browser::set_timeout(f.borrow().as_ref().unwrap(), delay);
+    }) as Box<dyn FnMut()>));
+
+    browser::set_timeout(
+        g.borrow()
+            .as_ref()
+            .ok_or_else(|| anyhow!("Worker: Loop is None"))?,
+        0,
+    )?;
+    Ok(())
+}
+
+pub(crate) fn apply(running: &mut Running, command: Command) {
+    match command {
+        Command::Key(code) => running.set_key_code(code),
+        Command::Reset => running.reset(ResetMode::Pc),
+        Command::PowerCycle => running.reset(ResetMode::PowerCycle),
+        Command::Load(instructions) => running.load_program(&instructions),
+        Command::Pause => running.pause(),
+        Command::Resume => running.resume(),
+        Command::Step(cycles) => running.step(cycles),
+        Command::SetSpeed(cycles_per_frame) => running.set_speed(cycles_per_frame),
+        Command::SetTimeBudget(time_budget) => running.set_time_budget(time_budget),
+        Command::SetRam(address, value) => running.machine().set_ram(address, value),
+        Command::ToggleBreakpoint(address) => running.toggle_breakpoint(address),
+        Command::TypeText(text, cycles_per_key) => running.type_text(&text, cycles_per_key),
+        // taken in the loop, to go back in the frame
+        Command::TakeSnapshot(_) | Command::ShowRam(_) => {}
+        Command::Restore(text) => match Snapshot::from_text(&text) {
+            Ok(snapshot) => running.restore(&snapshot),
+            Err(err) => {
+                log!("Could not restore the snapshot: {}", err);
+            }
+        },
+    }
+}
+
+pub(crate) fn status(running: &mut Running) -> Status {
+    let machine = running.machine();
+    Status {
+        pc: machine.get_pc(),
+        a: machine.get_a(),
+        d: machine.get_d(),
+        paused: running.is_paused(),
+        speed: running.speed(),
+        time_budget: running.time_budget(),
+        instructions_per_second: running.instructions_per_second(),
+        breakpoints: running.breakpoints(),
+        typing: running.is_typing(),
+    }
+}
+
+// a pixel per bit, the lowest bit leftmost
+fn screen_words(screen: &[bool]) -> Vec<u16> {
+    screen
+        .chunks_exact(16)
+        .map(|pixels| pixels.iter().rev().fold(0, |word, pixel| word << 1 | *pixel as u16))
+        .collect()
+}
+
+// the page side of a computer running in a worker
+struct Host {
+    worker: Worker,
+    // what the worker told last
+    status: Status,
+    // the .hack text of the program last sent, for a link to it
+    program: String,
+    // the last snapshot the worker sent for the autosave
+    autosave: Option<String>,
+    window: Window,
+    // commands sent before the first frame, when the worker may not listen yet
+    pending: Option<Vec<String>>,
+}
+
+thread_local! {
+    static HOST: RefCell<Option<Host>> = const { RefCell::new(None) };
+}
+
+pub fn is_host() -> bool {
+    HOST.with(|host| host.borrow().is_some())
+}
+
+pub fn send(command: Command) -> Result<()> {
+    HOST.with(|host| {
+        let mut host = host.borrow_mut();
+        let host = host.as_mut().ok_or_else(|| anyhow!("Worker: Not started"))?;
+        // the next frame tells these too, but the page shouldn't see the old values until then
+        match command {
+            Command::Pause => host.status.paused = true,
+            Command::Resume => host.status.paused = false,
+            Command::SetSpeed(cycles_per_frame) => {
+                host.status.speed = cycles_per_frame.max(1);
+                host.status.time_budget = 0.0;
+            }
+            Command::SetTimeBudget(time_budget) => host.status.time_budget = time_budget.max(0.0),
+            Command::ToggleBreakpoint(address) => {
+                if let Some(i) = host.status.breakpoints.iter().position(|breakpoint| *breakpoint == address) {
+                    host.status.breakpoints.remove(i);
+                } else {
+                    host.status.breakpoints.push(address);
+                    host.status.breakpoints.sort_unstable();
+                }
+            }
+            Command::TypeText(_, _) => host.status.typing = true,
+            Command::Load(ref instructions) => host.program = instructions.iter().map(|instruction| format!("{}\n", instruction)).collect(),
+            Command::Restore(ref text) => {
+                if let Ok(snapshot) = Snapshot::from_text(text) {
+                    host.program = link::hack_text(&snapshot.rom);
+                }
+            }
+            _ => {}
+        }
+        let message = serde_json::to_string(&command)?;
+        match host.pending.as_mut() {
+            Some(pending) => {
+                pending.push(message);
+                Ok(())
+            }
+            None => post(&host.worker, message),
+        }
+    })
+}
+
+fn post(worker: &Worker, message: String) -> Result<()> {
+    worker
+        .post_message(&message.into())
+        .map_err(|err| anyhow!("Error posting to the worker {:#?}", err))
+}
+
+pub fn program_of_worker() -> Result<String> {
+    HOST.with(|host| {
+        host.borrow()
+            .as_ref()
+            .map(|host| host.program.clone())
+            .ok_or_else(|| anyhow!("Worker: Not started"))
+    })
+}
+
+pub fn status_of_worker() -> Result<Status> {
+    HOST.with(|host| {
+        host.borrow()
+            .as_ref()
+            .map(|host| host.status.clone())
+            .ok_or_else(|| anyhow!("Worker: Not started"))
+    })
+}
+
+pub fn window_of_worker() -> Result<Window> {
+    HOST.with(|host| {
+        host.borrow()
+            .as_ref()
+            .map(|host| host.window.clone())
+            .ok_or_else(|| anyhow!("Worker: Not started"))
+    })
+}
+
+pub fn start_host(backend: Backend) -> Result<()> {
+    let worker = Worker::new(&format!("worker.js?backend={}", backend)).map_err(|err| anyhow!("Error starting worker.js {:#?}", err))?;
+    let (frame_sender, mut frame_receiver) = unbounded::<Frame>();
+    let onmessage = browser::closure_wrap(Box::new(move |event: MessageEvent| {
+        if let Some(frame) = event.data().as_string().and_then(|data| serde_json::from_str(&data).ok()) {
+            let _ = frame_sender.unbounded_send(frame);
+        }
+    }) as Box<dyn FnMut(MessageEvent)>);
+    worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    onmessage.forget();
+    HOST.with(|host| {
+        *host.borrow_mut() = Some(Host {
+            worker,
+            status: Status::default(),
+            program: String::new(),
+            autosave: None,
+            window: Window::default(),
+            pending: Some(Vec::new()),
+        })
+    });
+    // as engine does, a program shared as a link or else where the page was left
+    if !engine::load_link_of_page() {
+        if let Ok(Some(text)) = storage::load(storage::AUTOSAVE) {
+            send(Command::Restore(text))?;
+        }
+    }
+    storage::autosave_on_page_hide(|| HOST.with(|host| host.borrow().as_ref().and_then(|host| host.autosave.clone())))?;
+
+    let mut keyevent_receiver = engine::prepare_input()?;
+    let mut reset_receiver = engine::prepare_reset()?;
+    let mut keystate = KeyState::new();
+    let mut key_code = 0;
+    let mut renderer = Renderer::new(browser::context()?);
+    let mut debugger = Debugger::new()?;
+
+    let f: Rc<RefCell<Option<LoopClosure>>> = Rc::new(RefCell::new(None));
+    let g = f.clone();
+    *g.borrow_mut() = Some(browser::create_raf_closure(move |_perf: f64| {
+        engine::process_input(&mut keystate, &mut keyevent_receiver);
+        if keystate.code() != key_code {
+            key_code = keystate.code();
+            let _ = send(Command::Key(key_code));
+        }
+        while let Ok(reset_mode) = reset_receiver.try_recv() {
+            let _ = send(match reset_mode {
+                ResetMode::Pc => Command::Reset,
+                ResetMode::PowerCycle => Command::PowerCycle,
+            });
+        }
+
+        while let Ok(frame) = frame_receiver.try_recv() {
+            HOST.with(|host| {
+                if let Some(host) = host.borrow_mut().as_mut() {
+                    host.status = frame.status;
+                    for message in host.pending.take().unwrap_or_default() {
+                        let _ = post(&host.worker, message);
+                    }
+                    if frame.autosave.is_some() {
+                        host.autosave = frame.autosave;
+                    }
+                    host.window = frame.window;
+                }
+            });
+            if let Some(screen) = frame.screen {
+                renderer.draw_words(&screen);
+            }
+            if let Some((slot, text)) = frame.snapshot {
+                if let Err(err) = storage::save(&slot, &text) {
+                    log!("Could not save the snapshot {:#?}", err);
+                }
+            }
+        }
+        let _ = renderer.present();
+        let _ = debugger.update_from_worker();
+
+        let _ = browser::request_animation_frame(f.borrow().as_ref().unwrap());
+    }));
+
+    browser::request_animation_frame(
+        g.borrow()
+            .as_ref()
+            .ok_or_else(|| anyhow!("Worker: Loop is None"))?,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+const path = require("path");
+const CopyPlugin = require("copy-webpack-plugin");
+const WasmPackPlugin = require("@wasm-tool/wasm-pack-plugin");
+
+const dist = path.resolve(__dirname, "dist");
+
+module.exports = [
+  {
+    name: "index",
+    mode: "production",
+    entry: {
+      index: "./js/index.js"
+    },
+    output: {
+      path: dist,
+      filename: "[name].js"
+    },
+    devServer: {
+      contentBase: dist,
+    },
+    plugins: [
+      new CopyPlugin([
+        path.resolve(__dirname, "static")
+      ]),
+
+      new WasmPackPlugin({
+        crateDirectory: __dirname,
+      }),
+    ]
+  },
+  // the computer of a page opened with ?worker; built after index, which builds pkg
+  {
+    name: "worker",
+    dependencies: ["index"],
+    mode: "production",
+    target: "webworker",
+    entry: {
+      worker: "./js/worker.js"
+    },
+    output: {
+      path: dist,
+      filename: "[name].js",
+      chunkFilename: "worker.[id].js"
+    }
+  }
+];